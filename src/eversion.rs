@@ -0,0 +1,324 @@
+use crate::{
+    nstrip::EversionConfig,
+    sphere::{Eversible, Sto},
+    threejet::ThreeJet,
+    transform::Transform,
+    twojetvec::TwoJetVec,
+};
+
+/// Timestamps, in global time [0 <= T <= 1], at which each stage of the eversion begins.
+///
+/// A negative timestamp disables its stage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timeline {
+    /// Local time [0 <= t <= 1] of the bend-in stage, the straight cylinder bending into the round
+    /// sphere that corrugation starts from, to show whatever the global time, as evert.c's `-bend`
+    /// does; negative, the default, to follow the timeline.
+    pub bend:   f64,
+    pub corr:   f64,
+    pub push:   f64,
+    pub twist:  f64,
+    pub unpush: f64,
+    pub uncorr: f64,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self { bend: -1.00, corr: 0.00, push: 0.10, twist: 0.23, unpush: 0.60, uncorr: 0.93 }
+    }
+}
+
+impl Timeline {
+    pub fn new(bend: f64, corr: f64, push: f64, twist: f64, unpush: f64, uncorr: f64) -> Self {
+        Self { bend, corr, push, twist, unpush, uncorr }
+    }
+    /// Checks that the bend-in time, when enabled, lies within its stage.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.bend > 1.0 {
+            return Err(format!("bend time {} is a time within the bend-in stage and must lie in [0, 1], or be negative to follow the timeline", self.bend));
+        };
+        return Ok(());
+    }
+    /// Maps a global time onto the stage running at that time and its local time [0 <= t <= 1];
+    /// always the bend-in stage at `bend` when that is enabled.
+    ///
+    /// Returns `None` if `time` falls before every enabled stage.
+    pub fn stage(&self, time: f64) -> Option<(Sto, f64)> {
+        if self.bend >= 0.0 {
+            return Some((Sto::BendIn, self.bend));
+        };
+        /* time = (time - howfar) / chunk */
+        let end = |next: f64| -> f64 { if next < 0.0 { 1.0 } else { next } };
+        if time >= self.uncorr && self.uncorr >= 0.0 {
+            return Some((Sto::UnCorrugate, (time - self.uncorr) / (1.0 - self.uncorr)));
+        } else if time >= self.unpush && self.unpush >= 0.0 {
+            return Some((Sto::UnPush, (time - self.unpush) / (end(self.uncorr) - self.unpush)));
+        } else if time >= self.twist && self.twist >= 0.0 {
+            return Some((Sto::Twist, (time - self.twist) / (end(self.unpush) - self.twist)));
+        } else if time >= self.push && self.push >= 0.0 {
+            return Some((Sto::PushThrough, (time - self.push) / (end(self.twist) - self.push)));
+        } else if time >= self.corr && self.corr >= 0.0 {
+            return Some((Sto::Corrugate, (time - self.corr) / (end(self.push) - self.corr)));
+        };
+        return None;
+    }
+}
+
+/// Slice of the standard unit (u=0..1, v=0..1) to sample, and the spacing of the samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sampling {
+    pub umin: f64,
+    pub umax: f64,
+    pub du:   f64,
+    pub vmin: f64,
+    pub vmax: f64,
+    pub dv:   f64,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Self { umin: 0.0, umax: 1.0, du: 0.08333, vmin: 0.0, vmax: 1.0, dv: 0.08333 }
+    }
+}
+
+impl Sampling {
+    pub fn new(umin: f64, umax: f64, du: f64, vmin: f64, vmax: f64, dv: f64) -> Self {
+        Self { umin, umax, du, vmin, vmax, dv }
+    }
+}
+
+/// Surface of the sphere sampled over a [`Sampling`] at one instant of one stage.
+///
+/// `values[j][k]` holds the surface jet at `(u(j), v(k))` for `j` in `0..=jmax` and `k` in `0..=kmax`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    pub oper:   Sto,
+    pub t:      f64,
+    pub umin:   f64,
+    pub vmin:   f64,
+    pub du:     f64,
+    pub dv:     f64,
+    pub jmax:   usize,
+    pub kmax:   usize,
+    pub values: Vec<Vec<TwoJetVec>>,
+    pub speedu: Vec<Vec<f64>>,
+    pub speedv: Vec<f64>,
+}
+
+impl Grid {
+    #[inline] pub fn u(&self, j: usize) -> f64 { self.umin + self.du * j as f64 }
+    #[inline] pub fn v(&self, k: usize) -> f64 { self.vmin + self.dv * k as f64 }
+    /// Copy of the grid with `transform` applied to every jet.
+    ///
+    /// The speeds are left alone: they describe the parametrization, not the placement.
+    pub fn transformed(&self, transform: &Transform) -> Grid {
+        Grid {
+            values: self.values.iter().map(|row: &Vec<TwoJetVec>| row.iter().map(|p: &TwoJetVec| transform.apply_jet(p)).collect()).collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// The everting surface at a single (u, v): position, first and mixed derivatives, and unit normal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfacePoint {
+    pub u:        f64,
+    pub v:        f64,
+    pub position: [f64; 3],
+    pub du:       [f64; 3],
+    pub dv:       [f64; 3],
+    pub duv:      [f64; 3],
+    pub normal:   [f64; 3],
+    /// The jet everything above was read from.
+    pub jet:      TwoJetVec,
+}
+
+impl SurfacePoint {
+    pub fn new(u: f64, v: f64, jet: TwoJetVec) -> Self {
+        let (x, y, z) = (jet.x(), jet.y(), jet.z());
+        Self {
+            u, v,
+            position: [x.f(),   y.f(),   z.f()],
+            du:       [x.fu(),  y.fu(),  z.fu()],
+            dv:       [x.fv(),  y.fv(),  z.fv()],
+            duv:      [x.fuv(), y.fuv(), z.fuv()],
+            normal:   jet.normal(),
+            jet,
+        }
+    }
+}
+
+/// An everting sphere: what to sample, when each stage happens, and how it is cut into strips.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Eversion {
+    pub timeline: Timeline,
+    pub sampling: Sampling,
+    pub config:   EversionConfig,
+}
+
+impl Eversion {
+    pub fn new(timeline: Timeline, sampling: Sampling, config: EversionConfig) -> Self {
+        Self { timeline, sampling, config }
+    }
+    /// Samples the surface at local time `t` of stage `oper`.
+    pub fn grid(&self, oper: Sto, t: f64) -> Grid {
+        let Sampling { umin, umax, du: adu, vmin, vmax, dv: adv } = self.sampling;
+
+        let jmax: usize = (((umax - umin).abs() / adu + 0.5) as usize).max(1);
+        let kmax: usize = (((vmax - vmin).abs() / adv + 0.5) as usize).max(1);
+
+        let du: f64 = (umax - umin) / jmax as f64;
+        let dv: f64 = (vmax - vmin) / kmax as f64;
+
+        let mut values: Vec<Vec<TwoJetVec>> = Vec::with_capacity(jmax + 1);
+        let mut speedu: Vec<Vec<f64>>       = Vec::with_capacity(jmax + 1);
+        let mut speedv: Vec<f64>            = Vec::with_capacity(jmax + 1);
+
+        for j in 0..=jmax {
+            let u: f64 = umin + du * j as f64;
+            let row: Vec<TwoJetVec> = (0..=kmax).map(|k: usize| calc_speed_u(oper, u, vmin + dv * k as f64, t, &self.config)).collect();
+            speedu.push(row.iter().map(TwoJetVec::calc_speed_u).collect());
+            speedv.push(calc_speed_v(oper, u, t, &self.config));
+            values.push(row);
+        };
+
+        return Grid { oper, t, umin, vmin, du, dv, jmax, kmax, values, speedu, speedv };
+    }
+    /// Evaluates the surface at `(u, v)` at local time `t` of stage `oper`, without building a grid.
+    pub fn evaluate(&self, oper: Sto, t: f64, u: f64, v: f64) -> SurfacePoint {
+        SurfacePoint::new(u, v, calc_speed_u(oper, u, v, t, &self.config))
+    }
+    /// Evaluates the surface at `(u, v)` at global time `time`, if any stage is running then.
+    pub fn evaluate_at(&self, time: f64, u: f64, v: f64) -> Option<SurfacePoint> {
+        self.timeline.stage(time).map(|(oper, t): (Sto, f64)| self.evaluate(oper, t, u, v))
+    }
+    /// Samples the surface at global time `time`, if any stage is running then.
+    pub fn grid_at(&self, time: f64) -> Option<Grid> {
+        self.timeline.stage(time).map(|(oper, t): (Sto, f64)| self.grid(oper, t))
+    }
+}
+
+fn eval(oper: Sto, u: ThreeJet, v: ThreeJet, t: f64, config: &EversionConfig) -> TwoJetVec {
+    match oper {
+        Sto::Corrugate   => { u.corrugate(v, t, config) },
+        Sto::PushThrough => { u.push_through(v, t, config) },
+        Sto::Twist       => { u.twist(v, t, config) },
+        Sto::UnPush      => { u.unpush(v, t, config) },
+        Sto::UnCorrugate => { u.uncorrugate(v, t, config) },
+        Sto::BendIn      => { u.bend_in(v, t, config) },
+    }
+}
+
+pub(crate) fn calc_speed_v(oper: Sto, u: f64, t: f64, config: &EversionConfig) -> f64 {
+    let o: f64 = eval(oper, ThreeJet::new_simple(u, 1.0, 0.0), ThreeJet::new_simple(0.0, 0.0, 0.1), t, config).calc_speed_v();
+
+    if o != 0.0 {
+        return o;
+    } else {
+        return calc_speed_v(oper, u + if u < 1.0 { 1e-9 } else { -1e-9 }, t, config);
+    };
+}
+
+pub(crate) fn calc_speed_u(oper: Sto, u: f64, v: f64, t: f64, config: &EversionConfig) -> TwoJetVec {
+    return eval(oper, ThreeJet::new_simple(u, 1.0, 0.0), ThreeJet::new_simple(v, 0.0, 1.0), t, config);
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn evaluate_matches_grid() {
+        let eversion: crate::Eversion = crate::Eversion::default();
+        for oper in [crate::Sto::Corrugate, crate::Sto::PushThrough, crate::Sto::Twist, crate::Sto::UnPush, crate::Sto::UnCorrugate] {
+            let grid: crate::Grid = eversion.grid(oper, 0.5);
+            for (j, k) in [(1, 1), (3, 7), (grid.jmax - 1, grid.kmax)] {
+                let p: crate::eversion::SurfacePoint = eversion.evaluate(oper, 0.5, grid.u(j), grid.v(k));
+                assert_eq!(p.jet, grid.values[j][k], "{oper:?} at ({j}, {k})");
+                let n: f64 = p.normal.iter().map(|x: &f64| x * x).sum::<f64>();
+                assert!((n - 1.0).abs() < 1e-9, "{oper:?} at ({j}, {k}): |normal|^2 = {n}");
+            };
+        };
+    }
+    #[test]
+    fn evaluate_at_follows_timeline() {
+        let eversion: crate::Eversion = crate::Eversion::default();
+        let (oper, t): (crate::Sto, f64) = eversion.timeline.stage(0.4).unwrap();
+        assert_eq!(oper, crate::Sto::Twist);
+        assert_eq!(eversion.evaluate_at(0.4, 0.3, 0.6), Some(eversion.evaluate(oper, t, 0.3, 0.6)));
+        assert_eq!(eversion.evaluate_at(-0.1, 0.3, 0.6), None);
+    }
+    #[test]
+    fn strip_ends_where_the_next_begins() {
+        // the standard unit spans 1/n_strips of a turn about Z, so strip k + 1 starts where strip k ends
+        let eversion: crate::Eversion = crate::Eversion::default();
+        let turn: crate::Transform = crate::Transform::rotate_z(-2.0 * std::f64::consts::PI / eversion.config.n_strips as f64);
+        for oper in [crate::Sto::Corrugate, crate::Sto::PushThrough, crate::Sto::Twist, crate::Sto::UnPush, crate::Sto::UnCorrugate] {
+            for u in [0.2, 0.5, 0.9] {
+                let (start, end): ([f64; 3], [f64; 3]) = (eversion.evaluate(oper, 0.5, u, 0.0).position, eversion.evaluate(oper, 0.5, u, 1.0).position);
+                let turned: [f64; 3] = turn.apply_point(start);
+                for d in 0..3 { assert!((end[d] - turned[d]).abs() < 1e-9, "{oper:?} at u = {u}: {end:?} != {turned:?}"); };
+            };
+        };
+    }
+    #[test]
+    fn bend_in_ends_where_corrugate_starts() {
+        let eversion: crate::Eversion = crate::Eversion::default();
+        let bent: crate::Grid = eversion.grid(crate::Sto::BendIn, 1.0);
+        let corrugated: crate::Grid = eversion.grid(crate::Sto::Corrugate, 0.0);
+        for (bent, corrugated) in bent.values.iter().flatten().zip(corrugated.values.iter().flatten()) {
+            for (a, b) in [(bent.x(), corrugated.x()), (bent.y(), corrugated.y()), (bent.z(), corrugated.z())] {
+                assert!((a.f() - b.f()).abs() < 1e-12 && (a.fu() - b.fu()).abs() < 1e-12 && (a.fv() - b.fv()).abs() < 1e-12, "{a:?} != {b:?}");
+            };
+        };
+    }
+    #[test]
+    fn bend_time_shows_bend_in() {
+        let timeline: crate::Timeline = crate::Timeline { bend: 0.25, ..Default::default() };
+        assert_eq!(crate::Timeline::default().stage(0.0).map(|s: (crate::Sto, f64)| s.0), Some(crate::Sto::Corrugate));
+        for time in [-0.5, 0.0, 0.5, 1.0] {
+            assert_eq!(timeline.stage(time), Some((crate::Sto::BendIn, 0.25)));
+        };
+        assert_eq!(timeline.validate(), Ok(()));
+        assert_eq!(crate::Timeline::default().validate(), Ok(()));
+        assert!(crate::Timeline { bend: 1.5, ..Default::default() }.validate().is_err());
+        // the end of bending in is where the default timeline starts
+        let bent: crate::Eversion = crate::Eversion { timeline: crate::Timeline { bend: 1.0, ..Default::default() }, ..Default::default() };
+        let (before, after) = (bent.evaluate_at(0.7, 0.4, 0.3).unwrap(), crate::Eversion::default().evaluate_at(0.0, 0.4, 0.3).unwrap());
+        for d in 0..3 { assert!((before.position[d] - after.position[d]).abs() < 1e-12); };
+    }
+    #[test]
+    fn every_stage_ends_where_the_next_starts() {
+        let eversion: crate::Eversion = crate::Eversion::default();
+        for (end, start) in [(crate::Sto::Corrugate, crate::Sto::PushThrough), (crate::Sto::PushThrough, crate::Sto::Twist), (crate::Sto::Twist, crate::Sto::UnPush), (crate::Sto::UnPush, crate::Sto::UnCorrugate)] {
+            let (end, start): (crate::Grid, crate::Grid) = (eversion.grid(end, 1.0), eversion.grid(start, 0.0));
+            for (a, b) in end.values.iter().flatten().zip(start.values.iter().flatten()) {
+                for (a, b) in [(a.x(), b.x()), (a.y(), b.y()), (a.z(), b.z())] {
+                    assert!((a.f() - b.f()).abs() < 1e-12, "{:?} at 1 != {:?} at 0", end.oper, start.oper);
+                };
+            };
+        };
+    }
+    #[test]
+    fn lower_hemisphere_is_the_upper_turned_over() {
+        // what the '-' parts rely on: (u, v) -> (2 - u, -v) is the half turn about Y, at every stage
+        let eversion: crate::Eversion = crate::Eversion::default();
+        for time in [0.05, 0.2, 0.4, 0.7, 0.95] {
+            for (u, v) in [(0.3, 0.1), (0.8, 0.35), (0.95, 0.6)] {
+                let upper: [f64; 3] = eversion.evaluate_at(time, u, v).unwrap().position;
+                let lower: [f64; 3] = eversion.evaluate_at(time, 2.0 - u, -v).unwrap().position;
+                let turned: [f64; 3] = crate::Transform::rotate_y(std::f64::consts::PI).apply_point(upper);
+                for d in 0..3 { assert!((lower[d] - turned[d]).abs() < 1e-9, "T = {time} at ({u}, {v}): {lower:?} != {turned:?}"); };
+            };
+        };
+    }
+    #[test]
+    fn figure_eight_is_smooth_across_its_quarters() {
+        // the height of the figure-eight is mirrored about 2 between v = 1/4 and 3/4 and must not jump there
+        let eversion: crate::Eversion = crate::Eversion::default();
+        for oper in [crate::Sto::PushThrough, crate::Sto::Twist, crate::Sto::UnPush] {
+            for v in [0.25, 0.75] {
+                let (before, after) = (eversion.evaluate(oper, 0.5, 0.5, v - 1e-9).position, eversion.evaluate(oper, 0.5, 0.5, v + 1e-9).position);
+                for d in 0..3 { assert!((before[d] - after[d]).abs() < 1e-6, "{oper:?} at v = {v}: {before:?} != {after:?}"); };
+            };
+        };
+    }
+}
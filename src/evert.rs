@@ -1,28 +1,16 @@
 #![allow(clippy::needless_return)] // NO
 
 use clap::Parser;
-use sphere::Sto;
 
-mod twojet;
-mod twojetvec;
-
-mod threejet;
-mod threejetvec;
-
-mod nstrip;
-mod figureeight;
-
-mod c_gformat;
-mod points;
-
-mod sphere;
-mod spline;
-
-use crate::nstrip::{
-    ALLPARTS,
-	N_STRIPS,
-    BINARY,
-	EasyAtomic
+use evert_rs::{
+    nstrip::{ALLPARTS, EversionConfig},
+    spline,
+    export::{self, json::Snapshot},
+    Eversion,
+    StdSink,
+    Sampling,
+    Timeline,
+    Transform,
 };
 
 static LONG_ABOUT: &str = "Generate an everting sphere in Geomview/OOGL MESH or Bezier at:
@@ -44,15 +32,68 @@ With each OOGL MESH or Bezier element spanning range Ustep x Vstep.
 
 Produces radius-S sphere.";
 
+/// Output file formats
+#[derive(clap::ValueEnum, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Format {
+    /// Geomview/OOGL NMESH, or STBBP with --bezier
+    Oogl,
+    /// Wavefront OBJ polygons, one group per part; bicubic Bezier surfaces with --bezier
+    Obj,
+    /// STL triangles for 3D printing; binary with --binary
+    Stl,
+    /// PLY triangles with normals, (u, v) and speeds; binary little-endian with --binary
+    Ply,
+    /// glTF 2.0 animating T from 0 to 1 through morph targets; .glb with --binary
+    Gltf,
+    /// VTK XML unstructured grid of quads with normals, (u, v), speeds, strip, hemisphere and time
+    Vtu,
+    /// ParaView time series: one .vtu per frame written into --outdir, the .pvd collection to stdout
+    Pvd,
+    /// Geomview OFF polyhedron with the parts welded along their seams; see --normals and --colors
+    Off,
+    /// RenderMan RIB bicubic PatchMesh, or one Patch per cell with --bezier, carrying speeds/speedt
+    Rib,
+    /// POV-Ray scene of bicubic_patch objects, one per cell, grouped per part
+    Pov,
+    /// USD ASCII layer animating T from 0 to 1 with time-sampled points; NurbsPatch prims with --bezier
+    Usda,
+    /// IGES B-spline surfaces (entity 128), one per Bezier patch, placed
+    Iges,
+    /// X3D scene animating T from 0 to 1 through coordinate and normal interpolators on a TimeSensor
+    X3d,
+    /// VRML97 world animating T from 0 to 1 like --format x3d
+    Vrml,
+    /// OpenSCAD polyhedron() of the whole sphere, welded; warns when it is not a printable solid
+    Scad,
+    /// 3MF package of the whole sphere, welded; warns when it is not a printable solid
+    #[value(name = "3mf")]
+    #[serde(rename = "3mf")]
+    ThreeMf,
+    /// NumPy .npz of the unit strip's jets (f, fu, fv, fuv), (u, v), speeds and parameters
+    Npz,
+    /// JSON of the grid's positions, jets, normals and speeds, the part transforms, the stage and every argument
+    Json,
+    /// Geomview/OOGL VECT of the u = const and v = const lines, one color each; see --subdivisions
+    Vect,
+}
+
 /// Generate an everting sphere in Geomview/OOGL MESH or Bezier
-#[derive(Parser, Debug)]
+#[derive(Parser, serde::Serialize, Debug)]
 #[command(author, version, about, long_about = LONG_ABOUT)]
 #[clap(color = clap::ColorChoice::Auto)]
 struct Args {
     /// Timestep [0 <= T <= 1]
     #[arg(long, default_value_t = 0.00)]                 time:       f64,
     #[arg(long, default_value_t = 8)]                    nstrips:    i32,
-    #[arg(long, default_value_t = std::f64::consts::PI)] scale:      f64,
+    /// Radius S of the sphere, in millimetres in 3MF output
+    #[arg(long, default_value_t = 1.00)]                 scale:      f64,
+    /// Rotation X,Y,Z about each axis, in degrees, applied after scaling
+    #[arg(long, value_parser = parse_xyz, allow_hyphen_values = true, default_value = "0,0,0")]
+    rotate:     [f64; 3],
+    /// Translation X,Y,Z, applied after rotating
+    #[arg(long, value_parser = parse_xyz, allow_hyphen_values = true, default_value = "0,0,0")]
+    translate:  [f64; 3],
     #[arg(long, default_value_t = 0.00)]                 umin:       f64,
     #[arg(long, default_value_t = 1.00)]                 umax:       f64,
     /// Parameter for the surface of the sphere at T = 0
@@ -61,6 +102,8 @@ struct Args {
     #[arg(long, default_value_t = 1.00)]                 vmax:       f64,
     /// Parameter for the surface of the sphere at T = 0
     #[arg(long, default_value_t = 0.08333)]              dv:         f64,
+    /// Time [0 <= t <= 1] within the bend-in stage, the straight cylinder bending into the sphere ahead of corrugation, to show instead of --time and the stage timestamps, as evert.c's -bend; negative to skip
+    #[arg(long, default_value_t = -1.00, allow_negative_numbers = true)] bendtime: f64,
     /// Timestamp at which eversion begins to corrugate
     #[arg(long, default_value_t = 0.00)]                 corr:       f64,
     /// Timestamp at which eversion begins to push
@@ -75,67 +118,91 @@ struct Args {
     #[arg(long, required=false, default_value_t=false)]  bezier:     bool,
     /// Include transformations to replicate [0..1,0..1] to whole sphere
     #[arg(long, required=false, default_value_t=false)]  whole:      bool,
-    /// Undocumented switch, sets scene to true
+    /// Apply the replication transformations to the vertices (a LIST of copies) instead of emitting INST/TLIST
+    #[arg(long, required=false, default_value_t=false)]  bake:       bool,
+    /// Accepted for compatibility and ignored; the stage is always picked from the whole timeline
     #[arg(long, required=false, default_value_t=false)]  scene:      bool,
-    /// Undocumented swtich, sets scene to false
+    /// Accepted for compatibility and ignored; the stage is always picked from the whole timeline
     #[arg(long, required=false, default_value_t=false)]  bscene:     bool,
     /// Undocumented swtich, triggers binary output instead of human readable.
     #[arg(long, required=false, default_value_t=false)]  binary:     bool,
     /// Replicate selected portions or all if '*'; e.g. +0-0+2+4+6 for one pole-to-pole strip, plus every other strip in +Z hemisphere; numbers range [0..(nstrips-1)].
     #[arg(long, default_value_t = String::from("+0"))]   parts:      String,
+    /// Per-vertex normals in OFF output (NOFF)
+    #[arg(long, required=false, default_value_t=false)]  normals:    bool,
+    /// Per-vertex colors by latitude in OFF output (COFF)
+    #[arg(long, required=false, default_value_t=false)]  colors:     bool,
+    /// Output file format
+    #[arg(long, value_enum, default_value_t = Format::Oogl)] format:   Format,
+    /// Number of timesteps sampled over [0 <= T <= 1] by animated formats, at least 2
+    #[arg(long, default_value_t = 24)]                   frames:     usize,
+    /// Length in seconds of the animation of animated formats
+    #[arg(long, default_value_t = 10.0)]                 duration:   f64,
+    /// Directory the per-frame files of time series formats are written to
+    #[arg(long, default_value = ".")]                    outdir:     std::path::PathBuf,
+    /// Samples per grid cell along the parameter lines of VECT output
+    #[arg(long, default_value_t = 8)]                    subdivisions: usize,
+}
+
+fn parse_xyz(arg: &str) -> Result<[f64; 3], String> {
+    let xyz: Vec<f64> = arg.split(',').map(|x: &str| x.trim().parse::<f64>()).collect::<Result<_, _>>().map_err(|e| e.to_string())?;
+    return xyz.try_into().map_err(|_| String::from("expected three comma separated numbers X,Y,Z"));
 }
 
-fn main() {
+fn main() -> std::io::Result<()> {
     let args: Args = Args::parse();
    
     if ALLPARTS && !args.parts.is_empty() { eprintln!("Evert was built with the AllParts feature; parts will be ignored!") };
 
-    N_STRIPS.set(args.nstrips);
-    BINARY.set(args.binary);
-
-    let parts: Vec<char> = args.parts.as_bytes().iter().map(|x: &u8 | { *x as char }).collect();
-
-    let (umin, vmin, umax, vmax, adu, adv, _scale): (f64, f64, f64, f64, f64, f64, f64) = (args.umin, args.vmin, args.umax, args.vmax, args.du, args.dv, args.scale);
-
-    let time: f64 = args.time;
-
-    // -1 means don't do bendtime at all
-    // it seems the original code had te ability to bend.
-    // current implementation has some snippets here and there about it, and the math.
-    // but the logic seems to have been removed at some point.
-    // and since I'm doing a braindead one2one implementation of the logic on the original program 
-    // I can't derive the original implementation
-    // so here it lies as an eternal TODO to be hilighted by visual studio code...
-    // TODO: reimplement bendtime
-    let bendtime:    f64 =  -1.00;
-
-    let corrstart:   f64 =  args.corr;
-    let pushstart:   f64 =  args.push;
-    let twiststart:  f64 =  args.twist;
-    let unpushstart: f64 =  args.unpush;
-    let uncorrstart: f64 =  args.uncorr;
-
-    if bendtime >= 0.0 {
-        spline::print_scene(Sto::BendIn, umin, umax, adu, vmin, vmax, adv, bendtime, parts);
-    } else {
-        /* time = (time - howfar) / chunk */
-        if !args.bscene || args.scene {
-            if time >= uncorrstart && uncorrstart >= 0.0 {
-                let t: f64 = (time - uncorrstart) / (1.0 - uncorrstart);
-                spline::print_scene(Sto::UnCorrugate, umin, umax, adu, vmin, vmax, adv, t, parts);
-            };
-        } else if time >= unpushstart && unpushstart >= 0.0 {
-            let t: f64 = (time - unpushstart) / (if uncorrstart < 0.0 { 1.0 } else { uncorrstart } - unpushstart); 
-            spline::print_scene(Sto::UnPush, umin, umax, adu, vmin, vmax, adv, t, parts);
-        } else if time >= twiststart && twiststart >= 0.0 {
-            let t: f64 = (time - twiststart) / (if unpushstart < 0.0 { 1.0 } else { unpushstart } - twiststart);
-            spline::print_scene(Sto::Twist, umin, umax, adu, vmin, vmax, adv, t, parts);
-        } else if time >= pushstart && pushstart >= 0.0 {
-            let t: f64 = (time - pushstart) / (if twiststart < 0.0 { 1.0 } else { twiststart } - pushstart);
-            spline::print_scene(Sto::PushThrough, umin, umax, adu, vmin, vmax, adv, t, parts);
-        } else if time >= corrstart && corrstart >= 0.0 {
-            let t: f64 = (time - corrstart) / (if pushstart < 0.0 { 1.0 } else { pushstart } - corrstart);
-            spline::print_scene(Sto::Corrugate, umin, umax, adu, vmin, vmax, adv, t, parts);
+    let parts: Vec<char> = if args.whole || ALLPARTS { vec!['*'] } else { args.parts.as_bytes().iter().map(|x: &u8 | { *x as char }).collect() };
+
+    let transform: Transform = Transform::scale(args.scale)
+        .then(&Transform::rotate_x(args.rotate[0].to_radians()))
+        .then(&Transform::rotate_y(args.rotate[1].to_radians()))
+        .then(&Transform::rotate_z(args.rotate[2].to_radians()))
+        .then(&Transform::translate(args.translate[0], args.translate[1], args.translate[2]));
+
+    // these only chose between UnCorrugate alone and the earlier stages, which by default left
+    // every time before UnCorrugate empty; Timeline::stage now always considers every stage
+    let (_scene, _bscene): (bool, bool) = (args.scene, args.bscene);
+
+    let timeline: Timeline = Timeline::new(args.bendtime, args.corr, args.push, args.twist, args.unpush, args.uncorr);
+    timeline.validate().map_err(|e: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+
+    let eversion: Eversion = Eversion::new(
+        timeline,
+        Sampling::new(args.umin, args.umax, args.du, args.vmin, args.vmax, args.dv),
+        EversionConfig { transform, baked: args.bake, ..EversionConfig::new(args.nstrips, args.binary, args.bezier) },
+    );
+
+    let mut sink: StdSink = StdSink::new();
+
+    match args.format {
+        Format::Gltf => { return export::gltf::print_gltf(&eversion, args.frames, args.duration, parts, &mut sink); },
+        Format::Pvd  => { return export::vtk::print_pvd(&eversion, args.frames, parts, &args.outdir, &mut sink); },
+        Format::Usda => { return export::usd::print_usda(&eversion, args.frames, args.duration, parts, &mut sink); },
+        Format::X3d  => { return export::x3d::print_x3d(&eversion, args.frames, args.duration, parts, false, &mut sink); },
+        Format::Vrml => { return export::x3d::print_x3d(&eversion, args.frames, args.duration, parts, true, &mut sink); },
+        _ => {},
+    };
+    if let Some(grid) = eversion.grid_at(args.time) {
+        match args.format {
+            Format::Oogl => { spline::print_scene(&grid, parts, &eversion.config, &mut sink)? },
+            Format::Obj  => { export::obj::print_obj(&grid, parts, &eversion.config, &mut sink)? },
+            Format::Stl  => { export::stl::print_stl(&grid, parts, &eversion.config, &mut sink)? },
+            Format::Ply  => { export::ply::print_ply(&grid, parts, &eversion.config, &mut sink)? },
+            Format::Vtu  => { export::vtk::print_vtu(&grid, args.time, parts, &eversion.config, &mut sink)? },
+            Format::Off  => { export::off::print_off(&grid, parts, &eversion.config, args.normals, args.colors, &mut sink)? },
+            Format::Rib  => { export::rib::print_rib(&grid, parts, &eversion.config, &mut sink)? },
+            Format::Pov  => { export::pov::print_pov(&grid, parts, &eversion.config, &mut sink)? },
+            Format::Iges => { export::iges::print_iges(&grid, parts, &eversion.config, &mut sink)? },
+            Format::Scad => { export::scad::print_scad(&grid, &eversion.config, &mut sink)? },
+            Format::ThreeMf => { export::threemf::print_3mf(&grid, &eversion.config, &mut sink)? },
+            Format::Vect => { export::vect::print_lines(&grid, parts, &eversion.config, args.subdivisions, &mut sink)? },
+            Format::Json => { export::json::print_json(&Snapshot::new(&grid, args.time, parts, &eversion.config, &args)?, &mut sink)? },
+            Format::Npz  => { export::npz::print_npz(&grid, args.time, &eversion.config, &mut sink)? },
+            Format::Gltf | Format::Pvd | Format::Usda | Format::X3d | Format::Vrml => unreachable!(),
         };
-    }
+    };
+    return Ok(());
 }
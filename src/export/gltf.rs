@@ -0,0 +1,171 @@
+use std::io::Write;
+
+use crate::{
+    eversion::{Eversion, Grid},
+    export::{base64, pieces, sweep, Piece, Vertex},
+    sink::Sink,
+};
+
+/// Positions and normals of one frame.
+type Frame = (Vec<[f32; 3]>, Vec<[f32; 3]>);
+
+/// Positions and shading normals of every piece of one frame, concatenated.
+fn frame(pieces: &[Piece]) -> Frame {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals:   Vec<[f32; 3]> = Vec::new();
+    for piece in pieces {
+        positions.extend(piece.vertices.iter().map(|vertex: &Vertex| vertex.position.map(|x: f64| x as f32)));
+        normals.extend(piece.shading_normals().into_iter().map(|normal: [f64; 3]| normal.map(|x: f64| x as f32)));
+    };
+    return (positions, normals);
+}
+
+/// Binary buffer of the glTF file and the views into it.
+struct Buffer {
+    data:  Vec<u8>,
+    views: Vec<String>,
+    accessors: Vec<String>,
+}
+
+impl Buffer {
+    /// Appends `bytes` as a new buffer view and returns its index.
+    fn view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        let offset: usize = self.data.len();
+        self.data.extend_from_slice(bytes);
+        self.views.push(match target {
+            Some(target) => format!(r#"{{"buffer":0,"byteOffset":{offset},"byteLength":{},"target":{target}}}"#, bytes.len()),
+            None         => format!(r#"{{"buffer":0,"byteOffset":{offset},"byteLength":{}}}"#, bytes.len()),
+        });
+        return self.views.len() - 1;
+    }
+    /// Appends a VEC3 float accessor with its bounds and returns its index.
+    fn vec3(&mut self, values: &[[f32; 3]]) -> usize {
+        let bytes: Vec<u8> = values.iter().flatten().flat_map(|x: &f32| x.to_le_bytes()).collect();
+        let view: usize = self.view(&bytes, Some(34962));
+        let (mut min, mut max): ([f32; 3], [f32; 3]) = ([f32::MAX; 3], [f32::MIN; 3]);
+        for value in values {
+            for d in 0..3 { min[d] = min[d].min(value[d]); max[d] = max[d].max(value[d]); };
+        };
+        self.accessors.push(format!(
+            r#"{{"bufferView":{view},"componentType":5126,"count":{},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}}"#,
+            values.len(), min[0], min[1], min[2], max[0], max[1], max[2]
+        ));
+        return self.accessors.len() - 1;
+    }
+    /// Appends a SCALAR accessor of `component` type (5125 unsigned int, 5126 float) and returns its index.
+    fn scalar(&mut self, bytes: &[u8], count: usize, component: u32, target: Option<u32>, bounds: Option<(f32, f32)>) -> usize {
+        let view: usize = self.view(bytes, target);
+        self.accessors.push(match bounds {
+            Some((min, max)) => format!(r#"{{"bufferView":{view},"componentType":{component},"count":{count},"type":"SCALAR","min":[{min}],"max":[{max}]}}"#),
+            None             => format!(r#"{{"bufferView":{view},"componentType":{component},"count":{count},"type":"SCALAR"}}"#),
+        });
+        return self.accessors.len() - 1;
+    }
+}
+
+/// Writes the whole eversion as one glTF 2.0 asset, `.gltf` with an embedded buffer or, with
+/// `eversion.config.binary`, `.glb`.
+///
+/// The mesh is the first of `frames` samples of global time [0, 1]; every later sample is a morph
+/// target, and a `duration`-second animation blends through them in turn. The (u, v) grid, and so
+/// the topology, is the same at every time.
+pub fn print_gltf(eversion: &Eversion, frames: usize, duration: f64, parts: Vec<char>, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let samples: Vec<(f64, Grid)> = sweep(eversion, frames)?;
+    let first: &Grid = &samples[0].1;
+
+    let mut topology: Vec<u32> = Vec::new();
+    let mut base: usize = 0;
+    for piece in pieces(first, parts.clone(), &eversion.config)? {
+        for [a, b, c, d] in piece.quads() {
+            topology.extend([a, b, c, a, c, d].map(|i: usize| (i + base) as u32));
+        };
+        base += piece.vertices.len();
+    };
+    let frames: Vec<Frame> = samples.iter()
+        .map(|(_, grid): &(f64, Grid)| pieces(grid, parts.clone(), &eversion.config).map(|p: Vec<Piece>| frame(&p)))
+        .collect::<std::io::Result<_>>()?;
+
+    let mut buffer: Buffer = Buffer { data: Vec::new(), views: Vec::new(), accessors: Vec::new() };
+    let indices: usize = buffer.scalar(&topology.iter().flat_map(|i: &u32| i.to_le_bytes()).collect::<Vec<u8>>(), topology.len(), 5125, Some(34963), None);
+    let (positions, normals) = (&frames[0].0, &frames[0].1);
+    let position: usize = buffer.vec3(positions);
+    let normal: usize = buffer.vec3(normals);
+
+    let mut targets: Vec<String> = Vec::new();
+    for (p, n) in frames.iter().skip(1) {
+        let dp: Vec<[f32; 3]> = p.iter().zip(positions).map(|(p, b): (&[f32; 3], &[f32; 3])| [p[0] - b[0], p[1] - b[1], p[2] - b[2]]).collect();
+        let dn: Vec<[f32; 3]> = n.iter().zip(normals).map(|(n, b): (&[f32; 3], &[f32; 3])| [n[0] - b[0], n[1] - b[1], n[2] - b[2]]).collect();
+        targets.push(format!(r#"{{"POSITION":{},"NORMAL":{}}}"#, buffer.vec3(&dp), buffer.vec3(&dn)));
+    };
+
+    let mut body: String = format!(
+        r#"{{"asset":{{"version":"2.0","generator":"evert_rs"}},"scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0,"name":"evert"}}],"meshes":[{{"name":"evert","primitives":[{{"attributes":{{"POSITION":{position},"NORMAL":{normal}}},"indices":{indices},"mode":4{}}}]{}}}]"#,
+        if targets.is_empty() { String::new() } else { format!(r#","targets":[{}]"#, targets.join(",")) },
+        if targets.is_empty() { String::new() } else { format!(r#","weights":[{}]"#, vec!["0"; targets.len()].join(",")) },
+    );
+    if !targets.is_empty() {
+        let times: Vec<f32> = samples.iter().map(|(time, _): &(f64, Grid)| (time * duration) as f32).collect();
+        let weights: Vec<f32> = (0..times.len()).flat_map(|i: usize| (1..times.len()).map(move |target: usize| if target == i { 1.0 } else { 0.0 })).collect();
+        let input: usize = buffer.scalar(&times.iter().flat_map(|t: &f32| t.to_le_bytes()).collect::<Vec<u8>>(), times.len(), 5126, None, Some((times[0], times[times.len() - 1])));
+        let output: usize = buffer.scalar(&weights.iter().flat_map(|w: &f32| w.to_le_bytes()).collect::<Vec<u8>>(), weights.len(), 5126, None, None);
+        body += &format!(
+            r#","animations":[{{"name":"eversion","samplers":[{{"input":{input},"output":{output},"interpolation":"LINEAR"}}],"channels":[{{"sampler":0,"target":{{"node":0,"path":"weights"}}}}]}}]"#
+        );
+    };
+    let out: &mut dyn Write = sink.geometry();
+    if eversion.config.binary {
+        body += &format!(r#","accessors":[{}],"bufferViews":[{}],"buffers":[{{"byteLength":{}}}]}}"#, buffer.accessors.join(","), buffer.views.join(","), buffer.data.len());
+        let mut json: Vec<u8> = body.into_bytes();
+        while !json.len().is_multiple_of(4) { json.push(b' '); };
+        while !buffer.data.len().is_multiple_of(4) { buffer.data.push(0); };
+        out.write_all(b"glTF")?;
+        out.write_all(&2u32.to_le_bytes())?;
+        out.write_all(&((12 + 8 + json.len() + 8 + buffer.data.len()) as u32).to_le_bytes())?;
+        out.write_all(&(json.len() as u32).to_le_bytes())?;
+        out.write_all(b"JSON")?;
+        out.write_all(&json)?;
+        out.write_all(&(buffer.data.len() as u32).to_le_bytes())?;
+        out.write_all(b"BIN\0")?;
+        out.write_all(&buffer.data)?;
+    } else {
+        body += &format!(
+            r#","accessors":[{}],"bufferViews":[{}],"buffers":[{{"byteLength":{},"uri":"data:application/octet-stream;base64,{}"}}]}}"#,
+            buffer.accessors.join(","), buffer.views.join(","), buffer.data.len(), base64(&buffer.data)
+        );
+        writeln!(out, "{body}")?;
+    };
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn glb_chunks() {
+        use crate::{eversion::Eversion, export::tests::written, nstrip::EversionConfig, sink::Sink};
+        let eversion: Eversion = Eversion { config: EversionConfig { binary: true, ..EversionConfig::default() }, ..Eversion::default() };
+        let (glb, _): (Vec<u8>, String) = written(|sink: &mut dyn Sink| super::print_gltf(&eversion, 3, 1.0, vec!['+', '0'], sink)).unwrap();
+        let word = |at: usize| -> usize { u32::from_le_bytes(glb[at..at + 4].try_into().unwrap()) as usize };
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(word(4), 2);
+        assert_eq!(word(8), glb.len());
+        let json: usize = word(12);
+        assert_eq!(&glb[16..20], b"JSON");
+        assert_eq!(&glb[20 + json + 4..20 + json + 8], b"BIN\0");
+        assert_eq!(20 + json + 8 + word(20 + json), glb.len());
+        let bin: usize = word(20 + json);
+        let gltf: serde_json::Value = serde_json::from_slice(&glb[20..20 + json]).unwrap();
+        assert_eq!(gltf["buffers"][0]["byteLength"].as_u64(), Some(bin as u64));
+        let views: &Vec<serde_json::Value> = gltf["bufferViews"].as_array().unwrap();
+        assert!(views.iter().all(|view: &serde_json::Value| view["byteOffset"].as_u64().unwrap() + view["byteLength"].as_u64().unwrap() <= bin as u64));
+
+        // one morph target per frame after the first, weighted by one channel over every frame
+        let count = |accessor: &serde_json::Value| -> u64 { gltf["accessors"][accessor.as_u64().unwrap() as usize]["count"].as_u64().unwrap() };
+        let primitive: &serde_json::Value = &gltf["meshes"][0]["primitives"][0];
+        assert_eq!(primitive["targets"].as_array().unwrap().len(), 2);
+        assert_eq!(count(&primitive["attributes"]["POSITION"]), 13 * 13);
+        assert_eq!(count(&primitive["targets"][1]["NORMAL"]), 13 * 13);
+        let animation: &serde_json::Value = &gltf["animations"][0];
+        assert_eq!(animation["channels"][0]["target"]["path"], "weights");
+        assert_eq!((count(&animation["samplers"][0]["input"]), count(&animation["samplers"][0]["output"])), (3, 3 * 2));
+    }
+}
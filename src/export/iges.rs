@@ -0,0 +1,186 @@
+use std::io::Write;
+
+use crate::{
+    eversion::Grid,
+    export::{pieces, Piece},
+    nstrip::EversionConfig,
+    points::BrezierPoint,
+    sink::Sink,
+    twojetvec::TwoJetVec,
+};
+
+/// Rational B-spline surface.
+const ENTITY_BSPLINE_SURFACE: i32 = 128;
+
+fn hollerith(s: &str) -> String { format!("{}H{}", s.len(), s) }
+
+fn real(x: f64) -> String { format!("{x:.9E}") }
+
+/// `YYYYMMDD.HHNNSS` in UTC, the IGES form of a date and time.
+fn timestamp() -> String {
+    let secs: i64 = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d: std::time::Duration| d.as_secs() as i64);
+    let (days, rem): (i64, i64) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    // civil date from days since 1970-01-01, proleptic Gregorian
+    let z: i64 = days + 719468;
+    let era: i64 = z.div_euclid(146097);
+    let doe: i64 = z - era * 146097;
+    let yoe: i64 = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy: i64 = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp: i64 = (5 * doy + 2) / 153;
+    let day: i64 = doy - (153 * mp + 2) / 5 + 1;
+    let month: i64 = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year: i64 = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    return format!("{year:04}{month:02}{day:02}.{:02}{:02}{:02}", rem / 3600, rem % 3600 / 60, rem % 60);
+}
+
+/// Packs `params`, already delimited, into lines of at most `width` columns.
+fn pack(params: &[String], width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![String::new()];
+    for param in params {
+        if lines.last().is_some_and(|line: &String| !line.is_empty() && line.len() + param.len() > width) {
+            lines.push(String::new());
+        };
+        lines.last_mut().unwrap().push_str(param);
+    };
+    return lines;
+}
+
+/// Delimits `params` with `,` and a closing `;`.
+fn delimited(params: Vec<String>) -> Vec<String> {
+    let n: usize = params.len();
+    return params.into_iter().enumerate().map(|(i, p): (usize, String)| p + if i + 1 == n { ";" } else { "," }).collect();
+}
+
+/// Parameters of entity 128 for one bicubic Bezier patch: a single-span B-spline of degree 3
+/// both ways over [0, 1] x [0, 1], polynomial, control points u varying fastest.
+fn bspline_patch(patch: &[BrezierPoint; 16]) -> Vec<String> {
+    let mut params: Vec<String> = [ENTITY_BSPLINE_SURFACE, 3, 3, 3, 3, 0, 0, 1, 0, 0].map(|i: i32| i.to_string()).to_vec();
+    for _ in 0..2 {
+        params.extend([0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0].map(real));
+    };
+    params.extend([1.0; 16].map(real));
+    params.extend(patch.iter().flat_map(|p: &BrezierPoint| p.xyz()).map(real));
+    params.extend([0.0, 1.0, 0.0, 1.0].map(real));
+    return params;
+}
+
+/// Writes `grid` as an IGES 5.3 file of one rational B-spline surface (entity 128) per
+/// `print_spline` patch, with the part transforms and `config.transform` applied to the
+/// control points.
+///
+/// Entities are labelled with their part, as in the OOGL TLIST comments, and subscripted with
+/// the index of their cell.
+pub fn print_iges(grid: &Grid, parts: Vec<char>, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let pieces: Vec<Piece> = pieces(grid, parts, config)?;
+    let mut entities: Vec<(String, usize, Vec<String>)> = Vec::new();
+    let mut extent: f64 = 0.0;
+    for piece in pieces.iter() {
+        let v: &Vec<Vec<TwoJetVec>> = &piece.grid.values;
+        for j in 0..piece.grid.jmax {
+            for k in 0..piece.grid.kmax {
+                let patch: [BrezierPoint; 16] = v[j][k].brezier_patch(v[j][k + 1], v[j + 1][k], v[j + 1][k + 1], piece.grid.du, piece.grid.dv);
+                extent = patch.iter().flat_map(|p: &BrezierPoint| p.xyz()).fold(extent, |m: f64, x: f64| m.max(x.abs()));
+                entities.push((piece.name.clone(), j * piece.grid.kmax + k, bspline_patch(&patch)));
+            };
+        };
+    };
+    let out: &mut dyn Write = sink.geometry();
+
+    let start: Vec<String> = vec![format!("everting sphere, {:?} at t = {}", grid.oper, grid.t)];
+    let global: Vec<String> = pack(&delimited(vec![
+        hollerith(","), hollerith(";"),
+        hollerith("evert"), hollerith("evert.igs"), hollerith("evert_rs"), hollerith(env!("CARGO_PKG_VERSION")),
+        String::from("32"), String::from("38"), String::from("6"), String::from("308"), String::from("15"),
+        hollerith("evert"), real(1.0), String::from("6"), hollerith("M"), String::from("1"), real(0.01),
+        hollerith(&timestamp()), real(1e-6), real(extent), hollerith(""), hollerith(""),
+        String::from("11"), String::from("0"),
+    ]), 72);
+    for (i, line) in start.iter().enumerate() { writeln!(out, "{line:<72}S{:>7}", i + 1)?; };
+    for (i, line) in global.iter().enumerate() { writeln!(out, "{line:<72}G{:>7}", i + 1)?; };
+
+    let data: Vec<Vec<String>> = entities.iter().map(|(_, _, params): &(String, usize, Vec<String>)| pack(&delimited(params.clone()), 64)).collect();
+    let mut pointer: usize = 1;
+    for (i, ((label, subscript, _), lines)) in entities.iter().zip(data.iter()).enumerate() {
+        let first: [String; 9] = [
+            ENTITY_BSPLINE_SURFACE.to_string(), pointer.to_string(), String::from("0"), String::from("0"), String::from("0"),
+            String::from("0"), String::from("0"), String::from("0"), String::from("00000000"),
+        ];
+        let second: [String; 9] = [
+            ENTITY_BSPLINE_SURFACE.to_string(), String::from("0"), String::from("0"), lines.len().to_string(), String::from("0"),
+            String::new(), String::new(), label.clone(), subscript.to_string(),
+        ];
+        writeln!(out, "{}D{:>7}", first.iter().map(|f: &String| format!("{f:>8}")).collect::<String>(), 2 * i + 1)?;
+        writeln!(out, "{}D{:>7}", second.iter().map(|f: &String| format!("{f:>8}")).collect::<String>(), 2 * i + 2)?;
+        pointer += lines.len();
+    };
+    let mut seq: usize = 0;
+    for (i, lines) in data.iter().enumerate() {
+        for line in lines {
+            seq += 1;
+            writeln!(out, "{line:<64} {:>7}P{seq:>7}", 2 * i + 1)?;
+        };
+    };
+    let terminate: String = format!("S{:>7}G{:>7}D{:>7}P{:>7}", start.len(), global.len(), 2 * entities.len(), seq);
+    writeln!(out, "{terminate:<72}T{:>7}", 1)?;
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    /// Entity type, label, subscript and parameters of every entity of an IGES file.
+    fn read_iges(iges: &str) -> Vec<(i32, String, usize, Vec<String>)> {
+        let mut sections: std::collections::HashMap<char, Vec<&str>> = std::collections::HashMap::new();
+        let mut order: Vec<char> = Vec::new();
+        for line in iges.lines() {
+            assert_eq!(line.len(), 80, "{line:?}");
+            let section: char = line.as_bytes()[72] as char;
+            if order.last() != Some(&section) { order.push(section); };
+            let lines: &mut Vec<&str> = sections.entry(section).or_default();
+            lines.push(line);
+            assert_eq!(line[73..].trim().parse::<usize>().unwrap(), lines.len(), "sequence of {line:?}");
+        };
+        assert_eq!(order, vec!['S', 'G', 'D', 'P', 'T']);
+        let terminate: &str = sections[&'T'][0];
+        for (i, section) in ['S', 'G', 'D', 'P'].iter().enumerate() {
+            assert_eq!(&terminate[8 * i..8 * i + 1], section.to_string());
+            assert_eq!(terminate[8 * i + 1..8 * i + 8].trim().parse::<usize>().unwrap(), sections[section].len());
+        };
+        let global: String = sections[&'G'].iter().map(|line: &&str| &line[..72]).collect();
+        assert!(global.starts_with("1H,,1H;,"), "{global}");
+
+        let (directory, data): (&Vec<&str>, &Vec<&str>) = (&sections[&'D'], &sections[&'P']);
+        let field = |line: &str, i: usize| -> String { line[8 * i..8 * i + 8].trim().to_string() };
+        return directory.chunks(2).enumerate().map(|(i, de): (usize, &[&str])| {
+            let (pointer, count): (usize, usize) = (field(de[0], 1).parse().unwrap(), field(de[1], 3).parse().unwrap());
+            let lines: &[&str] = &data[pointer - 1..pointer - 1 + count];
+            for line in lines { assert_eq!(line[65..72].trim().parse::<usize>().unwrap(), 2 * i + 1); };
+            let text: String = lines.iter().map(|line: &&str| line[..64].trim_end()).collect();
+            assert!(text.ends_with(';'));
+            let params: Vec<String> = text.trim_end_matches(';').split(',').map(String::from).collect();
+            (field(de[0], 0).parse().unwrap(), field(de[1], 7), field(de[1], 8).parse().unwrap(), params)
+        }).collect();
+    }
+    #[test]
+    fn reads_back() {
+        use crate::{eversion::{Eversion, Grid}, export::{pieces, tests::text}, nstrip::EversionConfig, sink::Sink, transform::Transform};
+        let config: EversionConfig = EversionConfig { transform: Transform::translate(0.0, 0.0, 2.0), ..Default::default() };
+        let grid: Grid = Eversion::default().grid(crate::Sto::Twist, 0.6);
+        let entities = read_iges(&text(|sink: &mut dyn Sink| super::print_iges(&grid, vec!['+', '2', '-', '5'], &config, sink)));
+
+        let placed = pieces(&grid, vec!['+', '2', '-', '5'], &config).unwrap();
+        assert_eq!(entities.len(), 2 * grid.jmax * grid.kmax);
+        for (entity, params) in [(0, 0), (grid.jmax * grid.kmax - 1, 0), (grid.jmax * grid.kmax + 29, 1)].map(|(e, p): (usize, usize)| (&entities[e], p)) {
+            let (kind, label, subscript, values): &(i32, String, usize, Vec<String>) = entity;
+            assert_eq!((*kind, label.as_str()), (128, placed[params].name.as_str()));
+            let numbers: Vec<f64> = values.iter().map(|x: &String| x.parse::<f64>().unwrap()).collect();
+            assert_eq!(&numbers[..10], &[128.0, 3.0, 3.0, 3.0, 3.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+            assert_eq!(numbers.len(), 10 + 8 + 8 + 16 + 48 + 4);
+            let (j, k): (usize, usize) = (subscript / grid.kmax, subscript % grid.kmax);
+            let v = &placed[params].grid.values;
+            let patch = v[j][k].brezier_patch(v[j][k + 1], v[j + 1][k], v[j + 1][k + 1], grid.du, grid.dv);
+            for (i, point) in patch.iter().enumerate() {
+                for d in 0..3 { assert!((numbers[42 + 3 * i + d] - point.xyz()[d]).abs() < 1e-8, "patch {subscript} point {i}"); };
+            };
+        };
+    }
+}
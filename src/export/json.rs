@@ -0,0 +1,214 @@
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    eversion::Grid,
+    export::{selected_parts, Piece},
+    nstrip::EversionConfig,
+    sink::Sink,
+    sphere::Sto,
+    spline::Part,
+    transform::Transform,
+    twojet::TwoJet,
+    twojetvec::TwoJetVec,
+};
+
+/// One sampled instant of the eversion, with the run that produced it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// Every command line argument, by name.
+    pub args:  Value,
+    /// Global time T; the stage and its own time are `grid.oper` and `grid.t`.
+    pub time:  f64,
+    pub grid:  Grid,
+    /// Every selected part, `print_part_side`'s transform followed by `config.transform`; `unit` alone without parts.
+    pub parts: Vec<(String, Transform)>,
+}
+
+/// The document [`print_json`] writes.
+#[derive(Serialize, Deserialize)]
+struct Document {
+    args:  Value,
+    time:  f64,
+    stage: String,
+    t:     f64,
+    grid:  GridDocument,
+    parts: Vec<PartDocument>,
+}
+
+/// `u`, `v`, `positions` and `normals` are derived from the rest and not read back.
+#[derive(Serialize, Deserialize)]
+struct GridDocument {
+    umin:   f64,
+    vmin:   f64,
+    du:     f64,
+    dv:     f64,
+    jmax:   usize,
+    kmax:   usize,
+    #[serde(skip_deserializing)] u:         Vec<f64>,
+    #[serde(skip_deserializing)] v:         Vec<f64>,
+    #[serde(skip_deserializing)] positions: Vec<Vec<[f64; 3]>>,
+    #[serde(skip_deserializing)] normals:   Vec<Vec<[f64; 3]>>,
+    jets:   Vec<Vec<[[f64; 4]; 3]>>,
+    speedu: Vec<Vec<f64>>,
+    speedv: Vec<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PartDocument {
+    name:      String,
+    transform: [[f64; 4]; 4],
+}
+
+fn stage(name: &str) -> Result<Sto, String> {
+    return [Sto::Corrugate, Sto::PushThrough, Sto::Twist, Sto::UnPush, Sto::UnCorrugate, Sto::BendIn].into_iter()
+        .find(|sto: &Sto| format!("{sto:?}") == name)
+        .ok_or(format!("unknown stage {name}"));
+}
+
+/// Checks that `key` has `rows` rows of `columns` samples each.
+fn shape(key: &str, lengths: &[usize], rows: usize, columns: usize) -> Result<(), String> {
+    if lengths.len() != rows { return Err(format!("\"{key}\" has {} rows, not {rows}", lengths.len())); };
+    if let Some(j) = lengths.iter().position(|n: &usize| *n != columns) {
+        return Err(format!("row {j} of \"{key}\" has {} samples, not {columns}", lengths[j]));
+    };
+    return Ok(());
+}
+
+/// Whether `value` holds a `null`, which is how serde_json spells NaN and the infinities.
+fn has_null(value: &Value) -> bool {
+    return match value {
+        Value::Null => true,
+        Value::Array(items) => items.iter().any(has_null),
+        Value::Object(members) => members.values().any(has_null),
+        _ => false,
+    };
+}
+
+impl Snapshot {
+    /// `args` is anything serializable to a JSON object, normally the parsed command line.
+    pub fn new(grid: &Grid, time: f64, parts: Vec<char>, config: &EversionConfig, args: &impl Serialize) -> std::io::Result<Self> {
+        let parts: Vec<(String, Transform)> = if parts.is_empty() {
+            vec![(String::from("unit"), config.transform)]
+        } else {
+            selected_parts(parts, config)?.into_iter().map(|part: Part| (format!("{}{}", part.sign, part.strip), part.transform.then(&config.transform))).collect()
+        };
+        return Ok(Self { args: serde_json::to_value(args)?, time, grid: grid.clone(), parts });
+    }
+
+    fn document(&self) -> Document {
+        let g: &Grid = &self.grid;
+        let piece: Piece = Piece::new(String::from("unit"), None, g.clone());
+        let samples = |f: &dyn Fn(usize, usize) -> [f64; 3]| -> Vec<Vec<[f64; 3]>> {
+            (0..=g.jmax).map(|j: usize| (0..=g.kmax).map(|k: usize| f(j, k)).collect()).collect()
+        };
+        let jet = |c: TwoJet| -> [f64; 4] { [c.f(), c.fu(), c.fv(), c.fuv()] };
+        return Document {
+            args:  self.args.clone(),
+            time:  self.time,
+            stage: format!("{:?}", g.oper),
+            t:     g.t,
+            grid:  GridDocument {
+                umin: g.umin, vmin: g.vmin, du: g.du, dv: g.dv, jmax: g.jmax, kmax: g.kmax,
+                u: (0..=g.jmax).map(|j: usize| g.u(j)).collect(),
+                v: (0..=g.kmax).map(|k: usize| g.v(k)).collect(),
+                positions: samples(&|j: usize, k: usize| piece.vertices[piece.index(j, k)].position),
+                normals:   samples(&|j: usize, k: usize| piece.vertices[piece.index(j, k)].normal),
+                jets: g.values.iter().map(|row: &Vec<TwoJetVec>| row.iter().map(|p: &TwoJetVec| [jet(p.x()), jet(p.y()), jet(p.z())]).collect()).collect(),
+                speedu: g.speedu.clone(),
+                speedv: g.speedv.clone(),
+            },
+            parts: self.parts.iter().map(|(name, m): &(String, Transform)| PartDocument { name: name.clone(), transform: m.m }).collect(),
+        };
+    }
+
+    /// Reads back a document, checking that every array of samples has the shape of the grid.
+    fn from_document(document: Document) -> Result<Self, String> {
+        let g: GridDocument = document.grid;
+        let (rows, columns): (usize, usize) = (g.jmax + 1, g.kmax + 1);
+        shape("jets", &g.jets.iter().map(Vec::len).collect::<Vec<usize>>(), rows, columns)?;
+        shape("speedu", &g.speedu.iter().map(Vec::len).collect::<Vec<usize>>(), rows, columns)?;
+        if g.speedv.len() != rows { return Err(format!("\"speedv\" has {} rows, not {rows}", g.speedv.len())); };
+        let jet = |c: [f64; 4]| -> TwoJet { TwoJet::new(c[0], c[1], c[2], Some(c[3])) };
+        let grid: Grid = Grid {
+            oper: stage(&document.stage)?,
+            t: document.t,
+            umin: g.umin, vmin: g.vmin, du: g.du, dv: g.dv, jmax: g.jmax, kmax: g.kmax,
+            values: g.jets.into_iter().map(|row: Vec<[[f64; 4]; 3]>| row.into_iter().map(|[x, y, z]: [[f64; 4]; 3]| TwoJetVec::new(jet(x), jet(y), jet(z))).collect()).collect(),
+            speedu: g.speedu,
+            speedv: g.speedv,
+        };
+        let parts: Vec<(String, Transform)> = document.parts.into_iter().map(|part: PartDocument| (part.name, Transform::new(part.transform))).collect();
+        return Ok(Self { args: document.args, time: document.time, grid, parts });
+    }
+}
+
+/// Writes `snapshot` as an indented JSON document. Arrays of samples are indexed `[j][k]`, u then v;
+/// `jets` holds (f, fu, fv, fuv) of x, y and z, `positions` and `normals` are derived from them for
+/// convenience, and matrices multiply row vectors, the translation in the last row. Numbers are
+/// written in the shortest form that reads back to the same `f64`.
+///
+/// JSON has no NaN or infinities, so a snapshot holding one is refused rather than written as `null`.
+pub fn print_json(snapshot: &Snapshot, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let document: Value = serde_json::to_value(snapshot.document())?;
+    // the arguments are written as given; only the sampled instant has to be finite
+    if document.as_object().is_some_and(|members| members.iter().any(|(key, value): (&String, &Value)| key != "args" && has_null(value))) {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "the snapshot holds a NaN or an infinity, which JSON cannot represent"));
+    };
+    let out: &mut dyn Write = sink.geometry();
+    serde_json::to_writer_pretty(&mut *out, &document)?;
+    writeln!(out)?;
+    return out.flush();
+}
+
+/// Reads a document written by [`print_json`].
+pub fn read_json(text: &str) -> Result<Snapshot, String> {
+    return Snapshot::from_document(serde_json::from_str(text).map_err(|e: serde_json::Error| e.to_string())?);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{eversion::{Eversion, Grid}, nstrip::EversionConfig, sink::Sink, transform::Transform};
+
+    fn snapshot() -> super::Snapshot {
+        let config: EversionConfig = EversionConfig { transform: Transform::scale(2.0), ..Default::default() };
+        let grid: Grid = Eversion::default().grid(crate::Sto::UnPush, 0.25);
+        let args: serde_json::Value = serde_json::json!({ "time": 0.7, "parts": "+0-3" });
+        return super::Snapshot::new(&grid, 0.7, vec!['+', '0', '-', '3'], &config, &args).unwrap();
+    }
+    fn json(snapshot: &super::Snapshot) -> std::io::Result<String> {
+        let (text, _): (Vec<u8>, String) = crate::export::tests::written(|sink: &mut dyn Sink| super::print_json(snapshot, sink))?;
+        return Ok(String::from_utf8(text).unwrap());
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let mut snapshot: super::Snapshot = snapshot();
+        assert_eq!(super::read_json(&json(&snapshot).unwrap()).unwrap(), snapshot);
+        // tiny and huge numbers come back exactly
+        snapshot.time = 1e-300;
+        snapshot.grid.speedv[1] = 1.0 / 3.0e200;
+        let text: String = json(&snapshot).unwrap();
+        let document: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(document["time"].to_string(), "1e-300");
+        assert_eq!(super::read_json(&text).unwrap(), snapshot);
+    }
+    #[test]
+    fn rejects_what_does_not_fit() {
+        let mut snapshot: super::Snapshot = snapshot();
+        let text: String = json(&snapshot).unwrap();
+
+        let mut document: serde_json::Value = serde_json::from_str(&text).unwrap();
+        document["grid"]["jets"][2].as_array_mut().unwrap().pop();
+        let error: String = super::read_json(&document.to_string()).unwrap_err();
+        assert!(error.starts_with("row 2 of \"jets\""), "{error}");
+        let mut document: serde_json::Value = serde_json::from_str(&text).unwrap();
+        document["grid"]["speedv"].as_array_mut().unwrap().pop();
+        assert!(super::read_json(&document.to_string()).is_err());
+
+        snapshot.grid.speedu[3][4] = f64::NAN;
+        assert_eq!(json(&snapshot).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+}
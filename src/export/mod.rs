@@ -0,0 +1,338 @@
+//! Exporters for formats other than Geomview/OOGL.
+//!
+//! They all start from the same [`Grid`] as [`crate::spline::print_scene`], replicated over the
+//! selected parts into [`Piece`]s with the placement already applied to the vertices.
+
+use crate::{
+    eversion::{Eversion, Grid},
+    nstrip::EversionConfig,
+    points::BrezierPoint,
+    spline::{parse_parts, part_transforms, Part},
+    transform::Transform,
+    twojetvec::TwoJetVec,
+};
+
+pub mod obj;
+pub mod stl;
+pub mod ply;
+pub mod gltf;
+pub mod vtk;
+pub mod off;
+pub mod rib;
+pub mod pov;
+pub mod usd;
+pub mod iges;
+pub mod x3d;
+pub mod scad;
+pub mod threemf;
+pub mod npz;
+pub mod json;
+pub mod vect;
+
+/// One sample of the surface, placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    pub position: [f64; 3],
+    /// Unit normal, fu x fv of the placed jets; zero where the surface degenerates.
+    pub normal:   [f64; 3],
+    pub uv:       [f64; 2],
+    /// `speedu`, `speedv` of the grid at this sample, as measured on the unit sphere: unlike the
+    /// position, they stay the same under `--scale` and the rest of the placement.
+    pub speed:    [f64; 2],
+}
+
+/// One replica of the grid: the standard unit when no parts are selected, or one strip of one hemisphere.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Piece {
+    /// `+k`/`-k` after the strip and hemisphere, as in the OOGL TLIST comments, or `unit`.
+    pub name:     String,
+    pub part:     Option<Part>,
+    /// Placed copy of the grid the vertices were read from.
+    pub grid:     Grid,
+    /// `(jmax + 1) * (kmax + 1)` vertices, row by row along u, v varying fastest.
+    pub vertices: Vec<Vertex>,
+}
+
+impl Piece {
+    pub fn new(name: String, part: Option<Part>, grid: Grid) -> Self {
+        let mut vertices: Vec<Vertex> = Vec::with_capacity((grid.jmax + 1) * (grid.kmax + 1));
+        for (j, row) in grid.values.iter().enumerate() {
+            for (k, jet) in row.iter().enumerate() {
+                vertices.push(Vertex {
+                    position: [jet.x().f(), jet.y().f(), jet.z().f()],
+                    normal:   jet.normal(),
+                    uv:       [grid.u(j), grid.v(k)],
+                    speed:    [grid.speedu[j][k], grid.speedv[j]],
+                });
+            };
+        };
+        return Self { name, part, grid, vertices };
+    }
+    /// Placement of this replica: its part's transform, if it has one, followed by `config.transform`.
+    pub fn transform(&self, config: &EversionConfig) -> Transform {
+        return self.part.as_ref().map_or(config.transform, |part: &Part| part.transform.then(&config.transform));
+    }
+    /// Index into `vertices` of sample `(j, k)`.
+    #[inline] pub fn index(&self, j: usize, k: usize) -> usize { j * (self.grid.kmax + 1) + k }
+    /// Quads of the grid as vertex indices, counter-clockwise around `fu x fv`.
+    pub fn quads(&self) -> Vec<[usize; 4]> {
+        let mut quads: Vec<[usize; 4]> = Vec::with_capacity(self.grid.jmax * self.grid.kmax);
+        for j in 0..self.grid.jmax {
+            for k in 0..self.grid.kmax {
+                quads.push([self.index(j, k), self.index(j + 1, k), self.index(j + 1, k + 1), self.index(j, k + 1)]);
+            };
+        };
+        return quads;
+    }
+    /// Unit normals for shading: the jet normals, with those that degenerate, at the poles,
+    /// borrowed from the nearest sample along u that has one, or else from a facet around them.
+    pub fn shading_normals(&self) -> Vec<[f64; 3]> {
+        let (rows, row): (usize, usize) = (self.grid.jmax + 1, self.grid.kmax + 1);
+        return (0..self.vertices.len()).map(|i: usize| {
+            let (j, k): (usize, usize) = (i / row, i % row);
+            // the sample itself, then its neighbours along u one row further out at a time
+            let along: Option<[f64; 3]> = (0..rows)
+                .flat_map(|d: usize| [Some(j + d), j.checked_sub(d)])
+                .flatten()
+                .filter(|a: &usize| *a < rows)
+                .map(|a: usize| self.vertices[self.index(a, k)].normal)
+                .find(|n: &[f64; 3]| *n != [0.0; 3]);
+            return along.or_else(|| self.facet_normal_around(i)).unwrap_or([0.0; 3]);
+        }).collect();
+    }
+    /// Normal of the first facet with area of the quads around vertex `i`, if any has one.
+    fn facet_normal_around(&self, i: usize) -> Option<[f64; 3]> {
+        return self.quads().into_iter()
+            .filter(|quad: &[usize; 4]| quad.contains(&i))
+            .flat_map(|[a, b, c, d]: [usize; 4]| [[a, b, c], [a, c, d]])
+            .map(|triangle: [usize; 3]| { let [p, q, r] = triangle.map(|v: usize| self.vertices[v].position); facet_normal(p, q, r) })
+            .find(|n: &[f64; 3]| *n != [0.0; 3]);
+    }
+    /// The quads split into triangles, counter-clockwise around the jet normals of their corners.
+    ///
+    /// Triangles without area, such as half of every quad touching a pole, are left out.
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        let mut triangles: Vec<[usize; 3]> = Vec::with_capacity(2 * self.grid.jmax * self.grid.kmax);
+        for [a, b, c, d] in self.quads() {
+            for mut triangle in [[a, b, c], [a, c, d]] {
+                let [p, q, r] = triangle.map(|i: usize| self.vertices[i].position);
+                let n: [f64; 3] = facet_normal(p, q, r);
+                if n == [0.0; 3] { continue; };
+                let along: f64 = triangle.iter().map(|i: &usize| dot(n, self.vertices[*i].normal)).sum();
+                if along < 0.0 { triangle.swap(1, 2); };
+                triangles.push(triangle);
+            };
+        };
+        return triangles;
+    }
+}
+
+#[inline]
+pub(crate) fn dot(a: [f64; 3], b: [f64; 3]) -> f64 { a[0] * b[0] + a[1] * b[1] + a[2] * b[2] }
+
+#[inline]
+pub(crate) fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] { [a[0] - b[0], a[1] - b[1], a[2] - b[2]] }
+
+#[inline]
+pub(crate) fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+/// Unit normal of triangle `pqr`, counter-clockwise; zero if the triangle has no area to speak of.
+///
+/// Degenerate means the sine of the angle at `p` is below 1e-12, whatever the scale of the triangle.
+pub fn facet_normal(p: [f64; 3], q: [f64; 3], r: [f64; 3]) -> [f64; 3] {
+    let (e1, e2): ([f64; 3], [f64; 3]) = (sub(q, p), sub(r, p));
+    let n: [f64; 3] = cross(e1, e2);
+    let area2: f64 = dot(n, n);
+    if area2 <= 1e-24 * dot(e1, e1) * dot(e2, e2) {
+        return [0.0; 3];
+    };
+    return n.map(|x: f64| x / area2.sqrt());
+}
+
+/// Control net of the bicubic Bezier patches of `grid`, `(3 jmax + 1) x (3 kmax + 1)` points, u varying fastest.
+///
+/// Neighbouring patches share their edge rows, which `brezier_patch` builds from the same jets.
+pub fn control_net(grid: &Grid) -> Vec<[f64; 3]> {
+    let nu: usize = 3 * grid.jmax + 1;
+    let mut net: Vec<[f64; 3]> = vec![[0.0; 3]; nu * (3 * grid.kmax + 1)];
+    let v: &Vec<Vec<TwoJetVec>> = &grid.values;
+    for j in 0..grid.jmax {
+        for k in 0..grid.kmax {
+            let patch: [BrezierPoint; 16] = v[j][k].brezier_patch(v[j][k + 1], v[j + 1][k], v[j + 1][k + 1], grid.du, grid.dv);
+            for (i, point) in patch.iter().enumerate() {
+                let (row, column): (usize, usize) = (i / 4, i % 4);
+                net[(3 * k + row) * nu + 3 * j + column] = point.xyz();
+            };
+        };
+    };
+    return net;
+}
+
+/// The strips selected by `parts`, with the matrices `print_part_side` writes for them;
+/// a malformed selection is `InvalidInput`.
+pub fn selected_parts(parts: Vec<char>, config: &EversionConfig) -> std::io::Result<Vec<Part>> {
+    let partlist: Vec<u8> = parse_parts(parts, config.n_strips).map_err(|e: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    return Ok(part_transforms(&partlist, config.n_strips));
+}
+
+/// Replicates `grid` over the strips selected by `parts`, as [`crate::spline::print_scene`] would,
+/// and places every replica with `config.transform`.
+pub fn pieces(grid: &Grid, parts: Vec<char>, config: &EversionConfig) -> std::io::Result<Vec<Piece>> {
+    if parts.is_empty() {
+        return Ok(vec![Piece::new(String::from("unit"), None, grid.transformed(&config.transform))]);
+    };
+    return Ok(selected_parts(parts, config)?.into_iter().map(|part: Part| {
+        let transform: Transform = part.transform.then(&config.transform);
+        Piece::new(format!("{}{}", part.sign, part.strip), Some(part), grid.transformed(&transform))
+    }).collect());
+}
+
+/// How an exporter that can instance writes the replicas of the standard unit.
+pub enum Replicas {
+    /// Placed copies: the unit alone without parts, or with `config.baked` one per part.
+    Baked(Vec<Piece>),
+    /// The selected parts, each to place the one unplaced unit by its transform followed by `config.transform`.
+    Instanced(Vec<Part>),
+}
+
+/// Instances of the standard unit over the strips selected by `parts`, unless there are none or
+/// `config.baked` asks for the copies [`pieces`] makes.
+pub fn replicas(grid: &Grid, parts: Vec<char>, config: &EversionConfig) -> std::io::Result<Replicas> {
+    if parts.is_empty() || config.baked {
+        return Ok(Replicas::Baked(pieces(grid, parts, config)?));
+    };
+    return Ok(Replicas::Instanced(selected_parts(parts, config)?));
+}
+
+/// Grids at `frames` evenly spaced global times over [0, 1], with their global time.
+///
+/// Times before every enabled stage of the timeline are skipped; fewer than two frames, or no
+/// stage running at any of them, is an error.
+pub fn sweep(eversion: &Eversion, frames: usize) -> std::io::Result<Vec<(f64, Grid)>> {
+    if frames < 2 {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{frames} frames cannot span T = 0 to T = 1; at least 2 are needed")));
+    };
+    let last: f64 = (frames - 1) as f64;
+    let samples: Vec<(f64, Grid)> = (0..frames)
+        .map(|i: usize| i as f64 / last)
+        .filter_map(|time: f64| eversion.grid_at(time).map(|grid: Grid| (time, grid)))
+        .collect();
+    if samples.is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "no stage of the eversion is running between T = 0 and T = 1"));
+    };
+    return Ok(samples);
+}
+
+/// CRC-32 of `data`, as zip checks its members.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = !0;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 { crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 }; };
+    };
+    return !crc;
+}
+
+/// A zip archive of `members`, stored uncompressed.
+pub(crate) fn zip(members: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let (mut archive, mut directory): (Vec<u8>, Vec<u8>) = (Vec::new(), Vec::new());
+    for (name, data) in members {
+        let (offset, crc, size): (u32, u32, u32) = (archive.len() as u32, crc32(data), data.len() as u32);
+        // version 2.0, no flags, stored, 1980-01-01 00:00
+        let common: Vec<u8> = [&20u16.to_le_bytes()[..], &0u16.to_le_bytes(), &0u16.to_le_bytes(), &0u16.to_le_bytes(), &0x21u16.to_le_bytes(),
+            &crc.to_le_bytes(), &size.to_le_bytes(), &size.to_le_bytes(), &(name.len() as u16).to_le_bytes(), &0u16.to_le_bytes()].concat();
+        archive.extend(0x0403_4b50u32.to_le_bytes());
+        archive.extend(&common);
+        archive.extend(name.as_bytes());
+        archive.extend(data);
+        directory.extend(0x0201_4b50u32.to_le_bytes());
+        directory.extend(20u16.to_le_bytes());
+        directory.extend(&common);
+        // comment length, disk, internal and external attributes, then the local header
+        directory.extend([0u8; 10]);
+        directory.extend(offset.to_le_bytes());
+        directory.extend(name.as_bytes());
+    };
+    let (start, length, count): (u32, u32, u16) = (archive.len() as u32, directory.len() as u32, members.len() as u16);
+    archive.extend(directory);
+    archive.extend(0x0605_4b50u32.to_le_bytes());
+    archive.extend([0u8; 4]);
+    archive.extend(count.to_le_bytes());
+    archive.extend(count.to_le_bytes());
+    archive.extend(length.to_le_bytes());
+    archive.extend(start.to_le_bytes());
+    archive.extend([0u8; 2]);
+    return archive;
+}
+
+/// Standard base64 with padding, for data URIs.
+pub(crate) fn base64(data: &[u8]) -> String {
+    static ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut o: String = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b: [u32; 3] = [0, 1, 2].map(|i: usize| *chunk.get(i).unwrap_or(&0) as u32);
+        let n: u32 = (b[0] << 16) | (b[1] << 8) | b[2];
+        for i in 0..4 {
+            if i <= chunk.len() { o.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char); } else { o.push('='); };
+        };
+    };
+    return o;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sink::{Sink, WriterSink};
+
+    /// Runs an exporter into an in-memory sink, for the tests of every format: the geometry it
+    /// wrote and its diagnostics.
+    pub(super) fn written(print: impl FnOnce(&mut dyn Sink) -> std::io::Result<()>) -> std::io::Result<(Vec<u8>, String)> {
+        let mut sink: WriterSink<Vec<u8>, Vec<u8>> = WriterSink::new(Vec::new(), Vec::new());
+        print(&mut sink)?;
+        let (geometry, diagnostics): (Vec<u8>, Vec<u8>) = sink.into_inner();
+        return Ok((geometry, String::from_utf8(diagnostics).unwrap()));
+    }
+    /// The geometry a text exporter wrote.
+    pub(super) fn text(print: impl FnOnce(&mut dyn Sink) -> std::io::Result<()>) -> String {
+        return String::from_utf8(written(print).unwrap().0).unwrap();
+    }
+
+    #[test]
+    fn shading_normals_never_vanish() {
+        let eversion: crate::Eversion = crate::Eversion { sampling: crate::Sampling { umax: 2.0, ..Default::default() }, ..Default::default() };
+        let mut piece: super::Piece = super::Piece::new(String::from("unit"), None, eversion.grid(crate::Sto::Corrugate, 0.3));
+        let unit = |n: [f64; 3]| -> bool { (super::dot(n, n) - 1.0).abs() < 1e-9 };
+        let normals: Vec<[f64; 3]> = piece.shading_normals();
+        assert!(normals.iter().all(|n: &[f64; 3]| unit(*n)));
+        // the +Z pole borrows from the next row
+        for k in 0..=piece.grid.kmax {
+            assert_eq!(piece.vertices[piece.index(0, k)].normal, [0.0; 3]);
+            assert_eq!(normals[piece.index(0, k)], piece.vertices[piece.index(1, k)].normal);
+        };
+
+        // with no jet normal anywhere along u, a facet lends its own
+        for j in 0..=piece.grid.jmax {
+            let i: usize = piece.index(j, 4);
+            piece.vertices[i].normal = [0.0; 3];
+        };
+        let lent: Vec<[f64; 3]> = piece.shading_normals();
+        for j in 0..=piece.grid.jmax {
+            let n: [f64; 3] = lent[piece.index(j, 4)];
+            assert!(unit(n), "{n:?} at ({j}, 4)");
+            assert!(super::dot(n, normals[piece.index(j, 3)]) > 0.0, "{n:?} at ({j}, 4)");
+        };
+    }
+    #[test]
+    fn sweep_spans_the_timeline() {
+        let eversion: crate::Eversion = crate::Eversion::default();
+        let times: Vec<f64> = super::sweep(&eversion, 5).unwrap().into_iter().map(|(time, _): (f64, crate::Grid)| time).collect();
+        assert_eq!(times, vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+        for frames in [0, 1] {
+            assert_eq!(super::sweep(&eversion, frames).unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        };
+        // corrugation starting after T = 1 leaves nothing to sample
+        let late: crate::Eversion = crate::Eversion { timeline: crate::Timeline { corr: 1.5, push: 1.6, twist: 1.7, unpush: 1.8, uncorr: 1.9, ..Default::default() }, ..Default::default() };
+        assert_eq!(super::sweep(&late, 5).unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,101 @@
+use std::io::Write;
+
+use crate::{
+    eversion::Grid,
+    export::zip,
+    nstrip::EversionConfig,
+    sink::Sink,
+    twojet::TwoJet,
+    twojetvec::TwoJetVec,
+};
+
+/// One NumPy `.npy` (format 1.0) array of `shape`, C order, its `data` already in `descr`'s encoding.
+fn npy(descr: &str, shape: &[usize], data: Vec<u8>) -> Vec<u8> {
+    let dims: String = match shape {
+        [n] => format!("({n},)"),
+        _ => format!("({})", shape.iter().map(|n: &usize| n.to_string()).collect::<Vec<String>>().join(", ")),
+    };
+    let mut header: String = format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': {dims}, }}");
+    // magic, version and header length take 10 bytes; the data starts 64-byte aligned
+    let padding: usize = (64 - (10 + header.len() + 1) % 64) % 64;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+    let mut array: Vec<u8> = b"\x93NUMPY\x01\x00".to_vec();
+    array.extend((header.len() as u16).to_le_bytes());
+    array.extend(header.as_bytes());
+    array.extend(data);
+    return array;
+}
+
+fn float64(shape: &[usize], values: impl IntoIterator<Item = f64>) -> Vec<u8> {
+    return npy("<f8", shape, values.into_iter().flat_map(|x: f64| x.to_le_bytes()).collect());
+}
+
+/// A 0-d unicode array holding `text`.
+fn unicode(text: &str) -> Vec<u8> {
+    let chars: Vec<char> = text.chars().collect();
+    return npy(&format!("<U{}", chars.len().max(1)), &[], if chars.is_empty() { vec![0; 4] } else { chars.iter().flat_map(|c: &char| (*c as u32).to_le_bytes()).collect() });
+}
+
+/// Writes `grid`, with `config.transform` applied, as a NumPy `.npz` archive for `numpy.load`:
+///
+/// - `jet`: (nu, nv, 3, 4), the `TwoJetVec` at (u(j), v(k)) as (f, fu, fv, fuv) of x, y and z
+/// - `u`, `v`: (nu,) and (nv,), the sample coordinates
+/// - `speedu`: (nu, nv) and `speedv`: (nu,), as `calc_speed_u`/`calc_speed_v` give them
+/// - `time`, `stage`, `t`, `du`, `dv`, `nstrips`: 0-d, the global T, the running stage and its own t, the steps and the strip count
+///
+/// The jets are those of the unit strip; the parts are not replicated.
+pub fn print_npz(grid: &Grid, time: f64, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let placed: Grid = grid.transformed(&config.transform);
+    let (nu, nv): (usize, usize) = (grid.jmax + 1, grid.kmax + 1);
+    let jets = placed.values.iter().flatten().flat_map(|p: &TwoJetVec| [p.x(), p.y(), p.z()]).flat_map(|c: TwoJet| [c.f(), c.fu(), c.fv(), c.fuv()]);
+    let archive: Vec<u8> = zip(&[
+        ("jet.npy", float64(&[nu, nv, 3, 4], jets)),
+        ("u.npy", float64(&[nu], (0..nu).map(|j: usize| grid.u(j)))),
+        ("v.npy", float64(&[nv], (0..nv).map(|k: usize| grid.v(k)))),
+        ("speedu.npy", float64(&[nu, nv], grid.speedu.iter().flatten().copied())),
+        ("speedv.npy", float64(&[nu], grid.speedv.iter().copied())),
+        ("time.npy", float64(&[], [time])),
+        ("stage.npy", unicode(&format!("{:?}", grid.oper))),
+        ("t.npy", float64(&[], [grid.t])),
+        ("du.npy", float64(&[], [grid.du])),
+        ("dv.npy", float64(&[], [grid.dv])),
+        ("nstrips.npy", npy("<i8", &[], (config.n_strips as i64).to_le_bytes().to_vec())),
+    ]);
+    let out: &mut dyn Write = sink.geometry();
+    out.write_all(&archive)?;
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn aligned_headers() {
+        for shape in [&[][..], &[7], &[13, 13, 3, 4]] {
+            let array: Vec<u8> = super::float64(shape, vec![0.5; shape.iter().product()]);
+            let header_length: usize = u16::from_le_bytes([array[8], array[9]]) as usize;
+            assert_eq!((10 + header_length) % 64, 0);
+            assert_eq!(array.len(), 10 + header_length + 8 * shape.iter().product::<usize>());
+            assert_eq!(array[10 + header_length - 1], b'\n');
+        };
+        let header: String = String::from_utf8(super::float64(&[7], vec![0.0; 7])[10..].to_vec()).unwrap();
+        assert!(header.starts_with("{'descr': '<f8', 'fortran_order': False, 'shape': (7,), }"), "{header}");
+    }
+    #[test]
+    fn jet_layout() {
+        use crate::{eversion::{Eversion, Grid}, export::tests::written, nstrip::EversionConfig, sink::Sink};
+        let grid: Grid = Eversion::default().grid(crate::Sto::Twist, 0.3);
+        let (archive, _): (Vec<u8>, String) = written(|sink: &mut dyn Sink| super::print_npz(&grid, 0.5, &EversionConfig::default(), sink)).unwrap();
+        // the first member is jet.npy, stored
+        let name_length: usize = u16::from_le_bytes([archive[26], archive[27]]) as usize;
+        assert_eq!(&archive[30..30 + name_length], b"jet.npy");
+        let array: &[u8] = &archive[30 + name_length..];
+        let data: &[u8] = &array[10 + u16::from_le_bytes([array[8], array[9]]) as usize..];
+        let at = |j: usize, k: usize, c: usize, d: usize| -> f64 {
+            let i: usize = 8 * (((j * (grid.kmax + 1) + k) * 3 + c) * 4 + d);
+            f64::from_le_bytes(data[i..i + 8].try_into().unwrap())
+        };
+        let p = &grid.values[3][7];
+        assert_eq!([at(3, 7, 0, 0), at(3, 7, 1, 1), at(3, 7, 2, 2), at(3, 7, 2, 3)], [p.x().f(), p.y().fu(), p.z().fv(), p.z().fuv()]);
+    }
+}
@@ -0,0 +1,109 @@
+use std::io::Write;
+
+use crate::{
+    eversion::Grid,
+    export::{control_net, pieces, Piece},
+    nstrip::EversionConfig,
+    sink::Sink,
+};
+
+/// Writes `grid` as a Wavefront OBJ polygon mesh, one group per selected part; with
+/// `config.bezier`, as free-form bicubic Bezier surfaces instead.
+///
+/// Polygon vertices carry their shading normal and their (u, v) as texture coordinates.
+pub fn print_obj(grid: &Grid, parts: Vec<char>, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let pieces: Vec<Piece> = pieces(grid, parts, config)?;
+    let out: &mut dyn Write = sink.geometry();
+
+    writeln!(out, "# everting sphere, {:?} at t = {}", grid.oper, grid.t)?;
+    if config.bezier {
+        return print_surfaces(out, &pieces);
+    };
+    let mut base: usize = 1;
+    for piece in pieces.iter() {
+        writeln!(out, "g {}", piece.name)?;
+        for (vertex, normal) in piece.vertices.iter().zip(piece.shading_normals()) {
+            let ([x, y, z], [nx, ny, nz], [u, v]) = (vertex.position, normal, vertex.uv);
+            writeln!(out, "v {x:.6} {y:.6} {z:.6}")?;
+            writeln!(out, "vt {u:.6} {v:.6}")?;
+            writeln!(out, "vn {nx:.6} {ny:.6} {nz:.6}")?;
+        };
+        for quad in piece.quads() {
+            let [a, b, c, d] = quad.map(|i: usize| i + base);
+            writeln!(out, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c} {d}/{d}/{d}")?;
+        };
+        base += piece.vertices.len();
+    };
+    return out.flush();
+}
+
+/// One `surf` per piece: the control nets of `print_spline`, patch after patch sharing their
+/// edges, as a single Bezier surface with a span between every two samples of the grid.
+fn print_surfaces(out: &mut dyn Write, pieces: &[Piece]) -> std::io::Result<()> {
+    let mut base: usize = 1;
+    for piece in pieces {
+        let net: Vec<[f64; 3]> = control_net(&piece.grid);
+        let us: Vec<f64> = (0..=piece.grid.jmax).map(|j: usize| piece.grid.u(j)).collect();
+        let vs: Vec<f64> = (0..=piece.grid.kmax).map(|k: usize| piece.grid.v(k)).collect();
+        writeln!(out, "g {}", piece.name)?;
+        for [x, y, z] in net.iter() {
+            writeln!(out, "v {x:.6} {y:.6} {z:.6}")?;
+        };
+        writeln!(out, "cstype bezier")?;
+        writeln!(out, "deg 3 3")?;
+        // control points u varying fastest, as in the net
+        let indices: Vec<String> = (base..base + net.len()).map(|i: usize| i.to_string()).collect();
+        writeln!(out, "surf {} {} {} {} {}", us[0], us[us.len() - 1], vs[0], vs[vs.len() - 1], indices.join(" "))?;
+        writeln!(out, "parm u {}", us.iter().map(|u: &f64| u.to_string()).collect::<Vec<String>>().join(" "))?;
+        writeln!(out, "parm v {}", vs.iter().map(|v: &f64| v.to_string()).collect::<Vec<String>>().join(" "))?;
+        writeln!(out, "end")?;
+        base += net.len();
+    };
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{export::tests::text, sink::Sink};
+
+    #[test]
+    fn polygon_groups() {
+        let grid: crate::Grid = crate::Eversion::default().grid(crate::Sto::Twist, 0.3);
+        let obj: String = text(|sink: &mut dyn Sink| super::print_obj(&grid, vec!['+', '1', '-', '2'], &crate::nstrip::EversionConfig::default(), sink));
+
+        let lines = |prefix: &str| -> Vec<Vec<&str>> { obj.lines().filter(|line: &&str| line.starts_with(prefix)).map(|line: &str| line.split_whitespace().skip(1).collect()).collect() };
+        let (vertices, quads): (usize, usize) = ((grid.jmax + 1) * (grid.kmax + 1), grid.jmax * grid.kmax);
+        assert_eq!(lines("g "), vec![vec!["-2"], vec!["+1"]]);
+        assert_eq!((lines("v ").len(), lines("vt ").len(), lines("vn ").len(), lines("f ").len()), (2 * vertices, 2 * vertices, 2 * vertices, 2 * quads));
+        // unit normals everywhere, the poles included
+        for vn in lines("vn ") {
+            let n: Vec<f64> = vn.iter().map(|x: &&str| x.parse().unwrap()).collect();
+            assert!((n[0] * n[0] + n[1] * n[1] + n[2] * n[2] - 1.0).abs() < 1e-5, "{vn:?}");
+        };
+        // faces index v/vt/vn alike, the second group after the first group's vertices
+        let faces: Vec<Vec<usize>> = lines("f ").into_iter().map(|corners: Vec<&str>| corners.into_iter().map(|corner: &str| {
+            let indices: Vec<usize> = corner.split('/').map(|i: &str| i.parse().unwrap()).collect();
+            assert!(indices.iter().all(|i: &usize| *i == indices[0]), "{corner}");
+            indices[0]
+        }).collect()).collect();
+        assert!(faces[..quads].iter().flatten().all(|i: &usize| (1..=vertices).contains(i)));
+        assert!(faces[quads..].iter().flatten().all(|i: &usize| (vertices + 1..=2 * vertices).contains(i)));
+    }
+    #[test]
+    fn bezier_surfaces() {
+        let config: crate::nstrip::EversionConfig = crate::nstrip::EversionConfig { bezier: true, ..Default::default() };
+        let grid: crate::Grid = crate::Eversion::default().grid(crate::Sto::Twist, 0.3);
+        let obj: String = text(|sink: &mut dyn Sink| super::print_obj(&grid, vec!['+', '1', '-', '2'], &config, sink));
+
+        let (nu, nv): (usize, usize) = (3 * grid.jmax + 1, 3 * grid.kmax + 1);
+        assert_eq!(obj.lines().filter(|line: &&str| line.starts_with("v ")).count(), 2 * nu * nv);
+        let surfs: Vec<&str> = obj.lines().filter(|line: &&str| line.starts_with("surf ")).collect();
+        assert_eq!(surfs.len(), 2);
+        // the second surface picks up where the first one's control points end
+        assert_eq!(surfs[1].split_whitespace().nth(5), Some((nu * nv + 1).to_string().as_str()));
+        assert_eq!(surfs[1].split_whitespace().count(), 5 + nu * nv);
+        let parm: &str = obj.lines().find(|line: &&str| line.starts_with("parm u ")).unwrap();
+        assert_eq!(parm.split_whitespace().count(), 2 + grid.jmax + 1);
+        assert_eq!(obj.lines().filter(|line: &&str| *line == "end").count(), 2);
+    }
+}
@@ -0,0 +1,248 @@
+use std::{collections::HashMap, io::Write};
+
+use crate::{
+    eversion::Grid,
+    export::{dot, facet_normal, pieces, Piece, Vertex},
+    nstrip::EversionConfig,
+    sink::Sink,
+};
+
+/// Indexed polyhedron with the vertices shared between pieces welded together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Welded {
+    pub positions: Vec<[f64; 3]>,
+    /// Unit normals, the jet normals of every welded copy averaged; the facets' around the poles.
+    pub normals:   Vec<[f64; 3]>,
+    /// Latitude of every vertex over the whole sphere, 0 at the +Z pole through 2 at the -Z pole.
+    pub latitude:  Vec<f64>,
+    /// Triangles, counter-clockwise around `fu x fv`; only those collapsed by welding are left out.
+    pub faces:     Vec<[usize; 3]>,
+}
+
+impl Welded {
+    /// Welds the quads of `pieces`, split into triangles, merging vertices closer than a millionth of the extent of the surface.
+    pub fn new(pieces: &[Piece]) -> Self {
+        let vertices: Vec<(&Piece, &Vertex)> = pieces.iter().flat_map(|piece: &Piece| piece.vertices.iter().map(move |v: &Vertex| (piece, v))).collect();
+        let extent: f64 = vertices.iter().flat_map(|(_, v): &(&Piece, &Vertex)| v.position).fold(0.0, |m: f64, x: f64| m.max(x.abs()));
+        let eps: f64 = 1e-6 * extent.max(f64::MIN_POSITIVE);
+        let cell = |p: [f64; 3]| -> [i64; 3] { p.map(|x: f64| (x / eps).round() as i64) };
+
+        let mut welded: Self = Self { positions: Vec::new(), normals: Vec::new(), latitude: Vec::new(), faces: Vec::new() };
+        let mut cells: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        let mut index: Vec<usize> = Vec::with_capacity(vertices.len());
+        for (piece, vertex) in vertices.iter() {
+            let key: [i64; 3] = cell(vertex.position);
+            let mut found: Option<usize> = None;
+            'search: for dx in -1..=1 { for dy in -1..=1 { for dz in -1..=1 {
+                for &i in cells.get(&[key[0] + dx, key[1] + dy, key[2] + dz]).into_iter().flatten() {
+                    let d: [f64; 3] = crate::export::sub(welded.positions[i], vertex.position);
+                    if dot(d, d) <= eps * eps { found = Some(i); break 'search; };
+                };
+            }; }; };
+            let i: usize = found.unwrap_or_else(|| {
+                welded.positions.push(vertex.position);
+                welded.normals.push([0.0; 3]);
+                let mirrored: bool = piece.part.as_ref().is_some_and(|part| part.sign == '-');
+                welded.latitude.push(if mirrored { 2.0 - vertex.uv[0] } else { vertex.uv[0] });
+                cells.entry(key).or_default().push(welded.positions.len() - 1);
+                welded.positions.len() - 1
+            });
+            for d in 0..3 { welded.normals[i][d] += vertex.normal[d]; };
+            index.push(i);
+        };
+
+        let mut facets: Vec<[f64; 3]> = vec![[0.0; 3]; welded.positions.len()];
+        let mut base: usize = 0;
+        for piece in pieces {
+            // the winding of the parametrization, which, unlike the facets' own normals, agrees
+            // across every edge even where the surface folds tightly
+            for [a, b, c, d] in piece.quads() {
+                for triangle in [[a, b, c], [a, c, d]] {
+                    let face: [usize; 3] = triangle.map(|i: usize| index[i + base]);
+                    if face[0] == face[1] || face[1] == face[2] || face[2] == face[0] { continue; };
+                    let n: [f64; 3] = facet_normal(welded.positions[face[0]], welded.positions[face[1]], welded.positions[face[2]]);
+                    for i in face { for d in 0..3 { facets[i][d] += n[d]; }; };
+                    welded.faces.push(face);
+                };
+            };
+            base += piece.vertices.len();
+        };
+        for (normal, facet) in welded.normals.iter_mut().zip(facets) {
+            if dot(*normal, *normal) < 1e-24 { *normal = facet; };
+            let length: f64 = dot(*normal, *normal).sqrt();
+            if length > 0.0 { *normal = normal.map(|x: f64| x / length); };
+        };
+        return welded;
+    }
+    /// Signed volume enclosed, positive when the faces wind counter-clockwise seen from outside.
+    pub fn volume(&self) -> f64 {
+        let p: &Vec<[f64; 3]> = &self.positions;
+        return self.faces.iter().map(|[a, b, c]: &[usize; 3]| dot(p[*a], crate::export::cross(p[*b], p[*c]))).sum::<f64>() / 6.0;
+    }
+    /// Whether every edge borders exactly two faces, once in each direction: a closed, consistently wound surface.
+    pub fn is_manifold(&self) -> bool {
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        for [a, b, c] in self.faces.iter() {
+            for (p, q) in [(*a, *b), (*b, *c), (*c, *a)] { *edges.entry((p, q)).or_default() += 1; };
+        };
+        return edges.iter().all(|((p, q), count): (&(usize, usize), &usize)| *count == 1 && edges.get(&(*q, *p)) == Some(&1));
+    }
+    /// Whether any two faces without a common vertex cross each other.
+    pub fn self_intersects(&self) -> bool {
+        let p: &Vec<[f64; 3]> = &self.positions;
+        let lengths: Vec<f64> = self.faces.iter().map(|[a, b, _]: &[usize; 3]| dot(crate::export::sub(p[*a], p[*b]), crate::export::sub(p[*a], p[*b])).sqrt()).collect();
+        let size: f64 = 2.0 * (lengths.iter().sum::<f64>() / lengths.len().max(1) as f64).max(f64::MIN_POSITIVE);
+        let cell = |x: [f64; 3]| -> [i64; 3] { x.map(|c: f64| (c / size).floor() as i64) };
+        // broad phase: every face in each cell of a uniform grid its bounding box touches
+        let mut cells: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        for (i, face) in self.faces.iter().enumerate() {
+            let corners: [[i64; 3]; 3] = face.map(|v: usize| cell(p[v]));
+            let lo: [i64; 3] = [0, 1, 2].map(|d: usize| corners.iter().map(|c: &[i64; 3]| c[d]).min().unwrap());
+            let hi: [i64; 3] = [0, 1, 2].map(|d: usize| corners.iter().map(|c: &[i64; 3]| c[d]).max().unwrap());
+            for x in lo[0]..=hi[0] { for y in lo[1]..=hi[1] { for z in lo[2]..=hi[2] {
+                cells.entry([x, y, z]).or_default().push(i);
+            }; }; };
+        };
+        let triangle = |i: usize| -> [[f64; 3]; 3] { self.faces[i].map(|v: usize| p[v]) };
+        return cells.values().any(|members: &Vec<usize>| members.iter().enumerate().any(|(n, a): (usize, &usize)| members[n + 1..].iter().any(|b: &usize| {
+            !self.faces[*a].iter().any(|v: &usize| self.faces[*b].contains(v)) && triangles_cross(triangle(*a), triangle(*b))
+        })));
+    }
+    /// Number of distinct edges.
+    pub fn edges(&self) -> usize {
+        let mut edges: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+        for [a, b, c] in self.faces.iter() {
+            for (p, q) in [(*a, *b), (*b, *c), (*c, *a)] { edges.insert((p.min(q), p.max(q))); };
+        };
+        return edges.len();
+    }
+}
+
+/// Whether the segment `p`..`q` passes through the interior of `triangle` (Moller-Trumbore).
+fn segment_crosses(p: [f64; 3], q: [f64; 3], triangle: [[f64; 3]; 3]) -> bool {
+    const EPS: f64 = 1e-9;
+    let (d, e1, e2): ([f64; 3], [f64; 3], [f64; 3]) = (crate::export::sub(q, p), crate::export::sub(triangle[1], triangle[0]), crate::export::sub(triangle[2], triangle[0]));
+    let h: [f64; 3] = crate::export::cross(d, e2);
+    let det: f64 = dot(e1, h);
+    if det.abs() < f64::MIN_POSITIVE { return false; };
+    let s: [f64; 3] = crate::export::sub(p, triangle[0]);
+    let u: f64 = dot(s, h) / det;
+    let r: [f64; 3] = crate::export::cross(s, e1);
+    let v: f64 = dot(d, r) / det;
+    let t: f64 = dot(e2, r) / det;
+    return u > EPS && v > EPS && u + v < 1.0 - EPS && t > EPS && t < 1.0 - EPS;
+}
+
+/// Whether two triangles cross: an edge of either passes through the other.
+fn triangles_cross(a: [[f64; 3]; 3], b: [[f64; 3]; 3]) -> bool {
+    return (0..3).any(|i: usize| segment_crosses(a[i], a[(i + 1) % 3], b) || segment_crosses(b[i], b[(i + 1) % 3], a));
+}
+
+/// Size in model units, millimetres to a slicer, below which [`solid`] warns that the model
+/// is too small to print; the unit sphere is 2 across.
+pub const SMALLEST_PRINT: f64 = 10.0;
+
+/// Welds the whole sphere at `grid` into a solid to print, its faces turned to wind
+/// counter-clockwise seen from outside, and warns on the diagnostics of `sink` when it is not
+/// closed and manifold, when it crosses itself, as it does through most of the eversion, or when
+/// it is under [`SMALLEST_PRINT`] across.
+pub fn solid(grid: &Grid, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<Welded> {
+    let mut welded: Welded = Welded::new(&pieces(grid, vec!['*'], config)?);
+    if welded.volume() < 0.0 {
+        for face in welded.faces.iter_mut() { face.swap(1, 2); };
+    };
+    if !welded.is_manifold() {
+        writeln!(sink.diagnostics(), "warning: the sphere, {:?} at t = {}, is not a closed manifold solid", grid.oper, grid.t)?;
+    };
+    if welded.self_intersects() {
+        writeln!(sink.diagnostics(), "warning: the sphere, {:?} at t = {}, intersects itself; slicers may not print it as intended", grid.oper, grid.t)?;
+    };
+    let size: f64 = (0..3)
+        .map(|d: usize| welded.positions.iter().map(|p: &[f64; 3]| p[d]).fold(f64::NEG_INFINITY, f64::max) - welded.positions.iter().map(|p: &[f64; 3]| p[d]).fold(f64::INFINITY, f64::min))
+        .fold(0.0, f64::max);
+    if size < SMALLEST_PRINT {
+        writeln!(sink.diagnostics(), "warning: the sphere is {size:.3} units across, which 3MF and slicers read as millimetres; enlarge it with --scale")?;
+    };
+    return Ok(welded);
+}
+
+/// Blue at the +Z pole, white at the equator, gold at the -Z pole.
+pub(crate) fn latitude_color(latitude: f64) -> [f64; 4] {
+    let s: f64 = (latitude / 2.0).clamp(0.0, 1.0);
+    let (from, to): ([f64; 3], [f64; 3]) = if s < 0.5 { ([0.2, 0.4, 1.0], [1.0, 1.0, 1.0]) } else { ([1.0, 1.0, 1.0], [1.0, 0.75, 0.1]) };
+    let w: f64 = 2.0 * s - if s < 0.5 { 0.0 } else { 1.0 };
+    return [from[0] + w * (to[0] - from[0]), from[1] + w * (to[1] - from[1]), from[2] + w * (to[2] - from[2]), 1.0];
+}
+
+/// Writes `grid` as one OOGL OFF polyhedron, the parts welded together along their seams; NOFF
+/// with `normals`, COFF with `colors` (by latitude), both as CNOFF. Binary with `config.binary`.
+pub fn print_off(grid: &Grid, parts: Vec<char>, config: &EversionConfig, normals: bool, colors: bool, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let welded: Welded = Welded::new(&pieces(grid, parts, config)?);
+    let out: &mut dyn Write = sink.geometry();
+
+    writeln!(out, "{}{}OFF{}", if colors { "C" } else { "" }, if normals { "N" } else { "" }, if config.binary { " BINARY" } else { "" })?;
+    if config.binary {
+        for count in [welded.positions.len(), welded.faces.len(), welded.edges()] { out.write_all(&(count as i32).to_be_bytes())?; };
+    } else {
+        writeln!(out, "# everting sphere, {:?} at t = {}", grid.oper, grid.t)?;
+        writeln!(out, "{} {} {}", welded.positions.len(), welded.faces.len(), welded.edges())?;
+    };
+    for (i, position) in welded.positions.iter().enumerate() {
+        let mut values: Vec<f64> = position.to_vec();
+        if normals { values.extend(welded.normals[i]); };
+        if colors { values.extend(latitude_color(welded.latitude[i])); };
+        if config.binary {
+            for value in values { out.write_all(&(value as f32).to_be_bytes())?; };
+        } else {
+            writeln!(out, "{}", values.iter().map(|value: &f64| format!("{value:.6}")).collect::<Vec<String>>().join(" "))?;
+        };
+    };
+    for face in welded.faces.iter() {
+        if config.binary {
+            // vertex count, indices, then no face colors
+            for value in [3, face[0], face[1], face[2], 0] { out.write_all(&(value as i32).to_be_bytes())?; };
+        } else {
+            writeln!(out, "3 {} {} {}", face[0], face[1], face[2])?;
+        };
+    };
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn whole_sphere_is_watertight() {
+        use crate::{eversion::{Eversion, Grid}, export::{off::Welded, pieces}};
+        let eversion: Eversion = Eversion::default();
+        for time in [0.0, 0.3, 0.7] {
+            let grid: Grid = eversion.grid_at(time).unwrap();
+            let welded: Welded = Welded::new(&pieces(&grid, vec!['*'], &eversion.config).unwrap());
+            // every edge is shared by exactly two faces, in opposite directions
+            let mut edges: std::collections::HashMap<(usize, usize), i32> = std::collections::HashMap::new();
+            for [a, b, c] in welded.faces.iter() {
+                for (p, q) in [(*a, *b), (*b, *c), (*c, *a)] { *edges.entry((p, q)).or_default() += 1; };
+            };
+            for ((p, q), count) in edges.iter() {
+                assert_eq!((*count, edges.get(&(*q, *p))), (1, Some(&1)), "edge {p} {q} at T = {time}");
+            };
+            let euler: i64 = welded.positions.len() as i64 - welded.edges() as i64 + welded.faces.len() as i64;
+            assert_eq!(euler, 2, "at T = {time}");
+        };
+    }
+    #[test]
+    fn small_prints_are_flagged() {
+        use crate::{eversion::Eversion, nstrip::EversionConfig, sink::Sink, transform::Transform};
+        let warnings = |scale: f64| -> Vec<String> {
+            let config: EversionConfig = EversionConfig { transform: Transform::scale(scale), ..Default::default() };
+            let (_, warnings): (Vec<u8>, String) = crate::export::tests::written(|sink: &mut dyn Sink| super::solid(&Eversion::default().grid_at(0.0).unwrap(), &config, sink).map(|_: super::Welded| ())).unwrap();
+            return warnings.lines().map(String::from).collect();
+        };
+        // the round sphere at T = 0 is a clean solid, so only its size can be warned about
+        let small: Vec<String> = warnings(1.0);
+        assert_eq!(small.len(), 1);
+        let words: Vec<&str> = small[0].split_whitespace().collect();
+        let size: f64 = words[words.iter().position(|word: &&str| *word == "units").unwrap() - 1].parse().unwrap();
+        assert!((size - 2.0).abs() < 1e-3, "{small:?}");
+        assert_eq!(warnings(super::SMALLEST_PRINT / 2.0), Vec::<String>::new());
+    }
+}
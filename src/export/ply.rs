@@ -0,0 +1,120 @@
+use std::io::Write;
+
+use crate::{
+    eversion::Grid,
+    export::{pieces, Piece, Vertex},
+    nstrip::EversionConfig,
+    sink::Sink,
+};
+
+/// Vertex properties, in the order they are written.
+static PROPERTIES: [&str; 10] = ["x", "y", "z", "nx", "ny", "nz", "u", "v", "speedu", "speedv"];
+
+/// Writes `grid` as a PLY triangle mesh, ASCII or, with `config.binary`, binary little-endian.
+///
+/// Besides position, shading normal and (u, v), every vertex carries the `speedu`/`speedv` of the
+/// parametrization as extra float properties.
+pub fn print_ply(grid: &Grid, parts: Vec<char>, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let pieces: Vec<Piece> = pieces(grid, parts, config)?;
+    let vertices: Vec<&Vertex> = pieces.iter().flat_map(|piece: &Piece| piece.vertices.iter()).collect();
+    let normals: Vec<[f64; 3]> = pieces.iter().flat_map(|piece: &Piece| piece.shading_normals()).collect();
+    let mut faces: Vec<[usize; 3]> = Vec::new();
+    let mut base: usize = 0;
+    for piece in pieces.iter() {
+        faces.extend(piece.triangles().into_iter().map(|t: [usize; 3]| t.map(|i: usize| i + base)));
+        base += piece.vertices.len();
+    };
+    let out: &mut dyn Write = sink.geometry();
+
+    writeln!(out, "ply")?;
+    writeln!(out, "format {} 1.0", if config.binary { "binary_little_endian" } else { "ascii" })?;
+    writeln!(out, "comment everting sphere, {:?} at t = {}", grid.oper, grid.t)?;
+    writeln!(out, "element vertex {}", vertices.len())?;
+    for property in PROPERTIES { writeln!(out, "property float {property}")?; };
+    writeln!(out, "element face {}", faces.len())?;
+    writeln!(out, "property list uchar int vertex_indices")?;
+    writeln!(out, "end_header")?;
+
+    for (vertex, normal) in vertices.into_iter().zip(normals) {
+        let values: [f64; 10] = [
+            vertex.position[0], vertex.position[1], vertex.position[2],
+            normal[0], normal[1], normal[2],
+            vertex.uv[0], vertex.uv[1],
+            vertex.speed[0], vertex.speed[1],
+        ];
+        if config.binary {
+            for value in values { out.write_all(&(value as f32).to_le_bytes())?; };
+        } else {
+            writeln!(out, "{}", values.map(|value: f64| format!("{value:.6}")).join(" "))?;
+        };
+    };
+    for [a, b, c] in faces {
+        if config.binary {
+            out.write_all(&[3u8])?;
+            for i in [a, b, c] { out.write_all(&(i as i32).to_le_bytes())?; };
+        } else {
+            writeln!(out, "3 {a} {b} {c}")?;
+        };
+    };
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{export::tests::written, sink::Sink};
+
+    /// Header lines and the data after `end_header`.
+    fn ply(config: &crate::nstrip::EversionConfig) -> (crate::Grid, Vec<String>, Vec<u8>) {
+        let grid: crate::Grid = crate::Eversion::default().grid(crate::Sto::Corrugate, 0.5);
+        let (data, _): (Vec<u8>, String) = written(|sink: &mut dyn Sink| super::print_ply(&grid, vec!['+', '0', '-', '4'], config, sink)).unwrap();
+        let end: usize = data.windows(11).position(|w: &[u8]| w == b"end_header\n").unwrap() + 11;
+        let header: Vec<String> = String::from_utf8(data[..end].to_vec()).unwrap().lines().map(String::from).collect();
+        return (grid, header, data[end..].to_vec());
+    }
+    fn count(header: &[String], element: &str) -> usize {
+        let line: &String = header.iter().find(|line: &&String| line.starts_with(&format!("element {element} "))).unwrap();
+        return line.rsplit(' ').next().unwrap().parse().unwrap();
+    }
+    #[test]
+    fn ascii_counts() {
+        let (grid, header, data): (crate::Grid, Vec<String>, Vec<u8>) = ply(&crate::nstrip::EversionConfig::default());
+        assert_eq!(&header[..2], &["ply", "format ascii 1.0"]);
+        let (vertices, faces): (usize, usize) = (count(&header, "vertex"), count(&header, "face"));
+        assert_eq!(vertices, 2 * (grid.jmax + 1) * (grid.kmax + 1));
+        // the pole quads lose their degenerate half
+        assert_eq!(faces, 2 * (2 * grid.jmax * grid.kmax - grid.kmax));
+        let body: String = String::from_utf8(data).unwrap();
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), vertices + faces);
+        for line in &lines[..vertices] {
+            let values: Vec<f64> = line.split(' ').map(|x: &str| x.parse().unwrap()).collect();
+            assert_eq!(values.len(), super::PROPERTIES.len());
+            let n: f64 = values[3] * values[3] + values[4] * values[4] + values[5] * values[5];
+            assert!((n - 1.0).abs() < 1e-4, "normal of {line}");
+        };
+        assert!(lines[vertices..].iter().all(|line: &&str| line.starts_with("3 ") && line.split(' ').skip(1).all(|i: &str| i.parse::<usize>().unwrap() < vertices)));
+    }
+    #[test]
+    fn binary_counts() {
+        let (grid, header, data): (crate::Grid, Vec<String>, Vec<u8>) = ply(&crate::nstrip::EversionConfig { binary: true, ..Default::default() });
+        assert_eq!(header[1], "format binary_little_endian 1.0");
+        let (vertices, faces): (usize, usize) = (count(&header, "vertex"), count(&header, "face"));
+        assert_eq!((vertices, faces), (2 * (grid.jmax + 1) * (grid.kmax + 1), 2 * (2 * grid.jmax * grid.kmax - grid.kmax)));
+        assert_eq!(data.len(), vertices * 4 * super::PROPERTIES.len() + faces * (1 + 3 * 4));
+        let faces_at: usize = vertices * 4 * super::PROPERTIES.len();
+        assert!(data[faces_at..].chunks_exact(13).all(|face: &[u8]| face[0] == 3));
+    }
+    #[test]
+    fn speeds_ignore_the_placement() {
+        let vertices = |scale: f64| -> Vec<Vec<f64>> {
+            let (_, header, data) = ply(&crate::nstrip::EversionConfig { transform: crate::Transform::scale(scale), ..Default::default() });
+            let body: String = String::from_utf8(data).unwrap();
+            return body.lines().take(count(&header, "vertex")).map(|line: &str| line.split(' ').map(|x: &str| x.parse().unwrap()).collect()).collect();
+        };
+        let (unit, scaled): (Vec<Vec<f64>>, Vec<Vec<f64>>) = (vertices(1.0), vertices(3.0));
+        for (a, b) in unit.iter().zip(scaled.iter()) {
+            for d in 0..3 { assert!((3.0 * a[d] - b[d]).abs() < 1e-5, "{a:?} {b:?}"); };
+            assert_eq!(&a[3..], &b[3..]);
+        };
+    }
+}
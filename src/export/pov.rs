@@ -0,0 +1,117 @@
+use std::io::Write;
+
+use crate::{
+    eversion::Grid,
+    export::{pieces, replicas, Piece, Replicas, Vertex},
+    nstrip::EversionConfig,
+    points::BrezierPoint,
+    sink::Sink,
+    transform::Transform,
+    twojetvec::TwoJetVec,
+};
+
+/// Our right-handed, Z-up coordinates into POV-Ray's left-handed, Y-up ones: swap Y and Z.
+static TO_POV: &str = "matrix <1, 0, 0,  0, 0, 1,  0, 1, 0,  0, 0, 0>";
+
+fn vector(p: [f64; 3]) -> String { format!("<{:.6}, {:.6}, {:.6}>", p[0], p[1], p[2]) }
+
+/// POV-Ray `matrix` of `m`; both use row vectors, with the translation in the last row.
+fn matrix(m: &Transform) -> String {
+    let rows: Vec<String> = m.m.iter().map(|row: &[f64; 4]| format!("{:.6}, {:.6}, {:.6}", row[0], row[1], row[2])).collect();
+    return format!("matrix <{}>", rows.join(",  "));
+}
+
+/// One `bicubic_patch` per grid cell, with the control points of `print_spline`.
+fn print_patches(out: &mut dyn Write, grid: &Grid) -> std::io::Result<()> {
+    let v: &Vec<Vec<TwoJetVec>> = &grid.values;
+    for j in 0..grid.jmax {
+        for k in 0..grid.kmax {
+            let patch: [BrezierPoint; 16] = v[j][k].brezier_patch(v[j][k + 1], v[j + 1][k], v[j + 1][k + 1], grid.du, grid.dv);
+            let rows: Vec<String> = patch.chunks(4)
+                .map(|row: &[BrezierPoint]| row.iter().map(|p: &BrezierPoint| vector(p.xyz())).collect::<Vec<String>>().join(", "))
+                .collect();
+            writeln!(out, "    bicubic_patch {{ type 1 flatness 0.01 u_steps 3 v_steps 3,")?;
+            writeln!(out, "        {}", rows.join(",\n        "))?;
+            writeln!(out, "    }}")?;
+        };
+    };
+    return Ok(());
+}
+
+/// Writes `grid` as a complete POV-Ray scene of `bicubic_patch`es, with a camera and lights
+/// framing the selected parts.
+///
+/// The patches of the standard unit are declared once as `Strip` and every part is an `object`
+/// of it under its `print_part_side` matrix, unless `config.baked` writes every part's patches
+/// out in place. `Outside` and `Inside` are the textures of the two sides of the surface.
+pub fn print_pov(grid: &Grid, parts: Vec<char>, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let pieces: Vec<Piece> = pieces(grid, parts.clone(), config)?;
+    let positions: Vec<[f64; 3]> = pieces.iter().flat_map(|piece: &Piece| piece.vertices.iter().map(|v: &Vertex| v.position)).collect();
+    let mut center: [f64; 3] = [0.0; 3];
+    for p in positions.iter() { for d in 0..3 { center[d] += p[d] / positions.len() as f64; }; };
+    let radius: f64 = positions.iter().map(|p: &[f64; 3]| crate::export::dot(crate::export::sub(*p, center), crate::export::sub(*p, center)).sqrt()).fold(1e-6, f64::max);
+    let look_at: String = vector([center[0], center[2], center[1]]);
+    let out: &mut dyn Write = sink.geometry();
+
+    writeln!(out, "// everting sphere, {:?} at t = {}", grid.oper, grid.t)?;
+    writeln!(out, "#version 3.7;")?;
+    writeln!(out, "global_settings {{ assumed_gamma 1.0 }}")?;
+    writeln!(out, "background {{ color rgb <0.05, 0.05, 0.08> }}")?;
+    writeln!(out, "camera {{ location {look_at} + <0, {:.6}, {:.6}> look_at {look_at} angle 35 }}", 1.5 * radius, -4.0 * radius)?;
+    writeln!(out, "light_source {{ {look_at} + <{:.6}, {:.6}, {:.6}> color rgb 1 }}", -5.0 * radius, 8.0 * radius, -6.0 * radius)?;
+    writeln!(out, "light_source {{ {look_at} + <{:.6}, {:.6}, {:.6}> color rgb 0.4 shadowless }}", 6.0 * radius, -2.0 * radius, -4.0 * radius)?;
+    writeln!(out, "#declare Outside = texture {{ pigment {{ color rgb <0.85, 0.62, 0.18> }} finish {{ phong 0.5 }} }}")?;
+    writeln!(out, "#declare Inside = texture {{ pigment {{ color rgb <0.20, 0.36, 0.85> }} finish {{ phong 0.5 }} }}")?;
+
+    match replicas(grid, parts, config)? {
+        Replicas::Baked(pieces) => {
+            writeln!(out, "union {{")?;
+            for piece in pieces.iter() {
+                writeln!(out, "  // {}", piece.name)?;
+                writeln!(out, "  union {{")?;
+                print_patches(out, &piece.grid)?;
+                writeln!(out, "  }}")?;
+            };
+        },
+        Replicas::Instanced(parts) => {
+            writeln!(out, "#declare Strip = union {{")?;
+            print_patches(out, grid)?;
+            writeln!(out, "}}")?;
+            writeln!(out, "union {{")?;
+            for part in parts {
+                writeln!(out, "  // {}{} of {}", part.sign, part.strip, config.n_strips)?;
+                writeln!(out, "  object {{ Strip {} }}", matrix(&part.transform.then(&config.transform)))?;
+            };
+        },
+    };
+    writeln!(out, "  texture {{ Outside }}")?;
+    writeln!(out, "  interior_texture {{ Inside }}")?;
+    writeln!(out, "  {TO_POV}")?;
+    writeln!(out, "}}")?;
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn one_object_per_part() {
+        use crate::{eversion::{Eversion, Grid}, export::tests::text, nstrip::EversionConfig, sink::Sink};
+        let eversion: Eversion = Eversion::default();
+        let grid: Grid = eversion.grid(crate::Sto::PushThrough, 0.5);
+        let scene = |config: &EversionConfig| -> String {
+            return text(|sink: &mut dyn Sink| super::print_pov(&grid, vec!['+', '0', '-', '3', '+', '5'], config, sink));
+        };
+        let instanced: String = scene(&EversionConfig::default());
+        assert_eq!(instanced.matches("object { Strip matrix <").count(), 3);
+        assert_eq!(instanced.matches("bicubic_patch {").count(), grid.jmax * grid.kmax);
+        assert_eq!(instanced.matches('{').count(), instanced.matches('}').count());
+        let baked: String = scene(&EversionConfig { baked: true, ..EversionConfig::default() });
+        assert_eq!(baked.matches("bicubic_patch {").count(), 3 * grid.jmax * grid.kmax);
+        assert_eq!(baked.matches("Strip").count(), 0);
+        assert_eq!(baked.matches('{').count(), baked.matches('}').count());
+        // every part in its own union, named as in the TLIST comments
+        let names: Vec<&str> = baked.lines().filter_map(|line: &str| line.strip_prefix("  // ")).collect();
+        assert_eq!(names.len(), 3);
+        assert!(["+0", "-3", "+5"].iter().all(|name: &&str| names.contains(name)), "{names:?}");
+    }
+}
@@ -0,0 +1,121 @@
+use std::io::Write;
+
+use crate::{
+    eversion::Grid,
+    export::{control_net, replicas, Replicas},
+    nstrip::EversionConfig,
+    points::BrezierPoint,
+    sink::Sink,
+    transform::Transform,
+    twojetvec::TwoJetVec,
+};
+
+fn floats(values: impl IntoIterator<Item = f64>) -> String {
+    return values.into_iter().map(|x: f64| format!("{x:.6}")).collect::<Vec<String>>().join(" ");
+}
+
+/// Writes the patches of `grid`: one `PatchMesh`, or with `config.bezier` one `Patch` per cell as in STBBP.
+///
+/// `speeds`/`speedt` are `speedu`/`speedv` at the corners, `st` is (u, v).
+fn print_patches(out: &mut dyn Write, grid: &Grid, config: &EversionConfig) -> std::io::Result<()> {
+    if config.bezier {
+        let v: &Vec<Vec<TwoJetVec>> = &grid.values;
+        for j in 0..grid.jmax {
+            for k in 0..grid.kmax {
+                let patch: [BrezierPoint; 16] = v[j][k].brezier_patch(v[j][k + 1], v[j + 1][k], v[j + 1][k + 1], grid.du, grid.dv);
+                let corners: [(usize, usize); 4] = [(j, k), (j + 1, k), (j, k + 1), (j + 1, k + 1)];
+                writeln!(out, "Patch \"bicubic\" \"P\" [{}]", floats(patch.iter().flat_map(|p: &BrezierPoint| p.xyz())))?;
+                writeln!(out, "    \"speeds\" [{}] \"speedt\" [{}]",
+                    floats(corners.map(|(j, k): (usize, usize)| grid.speedu[j][k])),
+                    floats(corners.map(|(j, _): (usize, usize)| grid.speedv[j])))?;
+                writeln!(out, "    \"st\" [{}]", floats(corners.iter().flat_map(|(j, k): &(usize, usize)| [grid.u(*j), grid.v(*k)])))?;
+            };
+        };
+    } else {
+        let (nu, nv): (usize, usize) = (3 * grid.jmax + 1, 3 * grid.kmax + 1);
+        // varying values sit on the patch corners, the grid samples, u varying fastest
+        let corners: Vec<(usize, usize)> = (0..=grid.kmax).flat_map(|k: usize| (0..=grid.jmax).map(move |j: usize| (j, k))).collect();
+        writeln!(out, "PatchMesh \"bicubic\" {nu} \"nonperiodic\" {nv} \"nonperiodic\"")?;
+        writeln!(out, "    \"P\" [")?;
+        for row in control_net(grid).chunks(nu) {
+            writeln!(out, "        {}", floats(row.iter().flatten().copied()))?;
+        };
+        writeln!(out, "    ]")?;
+        writeln!(out, "    \"speeds\" [{}]", floats(corners.iter().map(|(j, k): &(usize, usize)| grid.speedu[*j][*k])))?;
+        writeln!(out, "    \"speedt\" [{}]", floats(corners.iter().map(|(j, _): &(usize, usize)| grid.speedv[*j])))?;
+        writeln!(out, "    \"st\" [{}]", floats(corners.iter().flat_map(|(j, k): &(usize, usize)| [grid.u(*j), grid.v(*k)])))?;
+    };
+    return Ok(());
+}
+
+/// Writes `grid` as a RenderMan RIB entity of bicubic Bezier patches, for `ReadArchive` inside a world block.
+///
+/// Replicas are `ObjectInstance`s of one retained object under each part's `ConcatTransform`
+/// (RIB shares Geomview's row-vector matrices), or, with `config.baked`, copies with the
+/// transforms already applied.
+pub fn print_rib(grid: &Grid, parts: Vec<char>, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let out: &mut dyn Write = sink.geometry();
+
+    writeln!(out, "##RenderMan RIB-Structure 1.1 Entity")?;
+    writeln!(out, "# everting sphere, {:?} at t = {}", grid.oper, grid.t)?;
+    writeln!(out, "Declare \"speeds\" \"varying float\"")?;
+    writeln!(out, "Declare \"speedt\" \"varying float\"")?;
+    writeln!(out, "AttributeBegin")?;
+    writeln!(out, "Basis \"bezier\" 3 \"bezier\" 3")?;
+    match replicas(grid, parts, config)? {
+        Replicas::Baked(pieces) => {
+            for piece in pieces {
+                writeln!(out, "AttributeBegin")?;
+                writeln!(out, "Attribute \"identifier\" \"name\" [\"{}\"]", piece.name)?;
+                print_patches(out, &piece.grid, config)?;
+                writeln!(out, "AttributeEnd")?;
+            };
+        },
+        Replicas::Instanced(parts) => {
+            writeln!(out, "ObjectBegin 1")?;
+            print_patches(out, grid, config)?;
+            writeln!(out, "ObjectEnd")?;
+            for part in parts {
+                let m: Transform = part.transform.then(&config.transform);
+                writeln!(out, "AttributeBegin")?;
+                writeln!(out, "Attribute \"identifier\" \"name\" [\"{}{}\"]", part.sign, part.strip)?;
+                writeln!(out, "ConcatTransform [{}]", floats(m.m.iter().flatten().copied()))?;
+                writeln!(out, "ObjectInstance 1")?;
+                writeln!(out, "AttributeEnd")?;
+            };
+        },
+    };
+    writeln!(out, "AttributeEnd")?;
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn patch_mesh_interpolates_the_grid() {
+        use crate::{eversion::{Eversion, Grid}, export::tests::text, nstrip::EversionConfig, sink::Sink};
+        let eversion: Eversion = Eversion::default();
+        let grid: Grid = eversion.grid(crate::Sto::Twist, 0.4);
+        let rib: String = text(|sink: &mut dyn Sink| super::print_rib(&grid, vec![], &EversionConfig::default(), sink));
+
+        let (nu, nv): (usize, usize) = (3 * grid.jmax + 1, 3 * grid.kmax + 1);
+        let meshes: Vec<Vec<&str>> = rib.lines().filter(|line: &&str| line.starts_with("PatchMesh ")).map(|line: &str| line.split_whitespace().collect()).collect();
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0][1..5], ["\"bicubic\"", nu.to_string().as_str(), "\"nonperiodic\"", nv.to_string().as_str()]);
+        let array = |name: &str| -> Vec<f64> {
+            let start: usize = rib.find(&format!("\"{name}\" [")).unwrap() + name.len() + 4;
+            let end: usize = start + rib[start..].find(']').unwrap();
+            rib[start..end].split_whitespace().map(|x: &str| x.parse::<f64>().unwrap()).collect()
+        };
+        let (p, speeds, speedt): (Vec<f64>, Vec<f64>, Vec<f64>) = (array("P"), array("speeds"), array("speedt"));
+        assert_eq!(p.len(), 3 * nu * nv);
+        assert_eq!((speeds.len(), speedt.len()), ((grid.jmax + 1) * (grid.kmax + 1), (grid.jmax + 1) * (grid.kmax + 1)));
+        // every third control point is a sample of the grid
+        for (j, k) in [(0, 0), (2, 5), (grid.jmax, grid.kmax)] {
+            let i: usize = 3 * ((3 * k) * nu + 3 * j);
+            let f: [f64; 3] = [grid.values[j][k].x().f(), grid.values[j][k].y().f(), grid.values[j][k].z().f()];
+            for d in 0..3 { assert!((p[i + d] - f[d]).abs() < 1e-6, "({j}, {k})"); };
+            assert!((speeds[k * (grid.jmax + 1) + j] - grid.speedu[j][k]).abs() < 1e-6);
+        };
+    }
+}
@@ -0,0 +1,70 @@
+use std::io::Write;
+
+use crate::{
+    eversion::Grid,
+    export::off::{solid, Welded},
+    nstrip::EversionConfig,
+    sink::Sink,
+};
+
+/// Writes the whole sphere at `grid`, welded, as one OpenSCAD `polyhedron()`.
+///
+/// OpenSCAD wants the points of every face clockwise seen from outside, the reverse of
+/// [`solid`]'s winding.
+pub fn print_scad(grid: &Grid, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let welded: Welded = solid(grid, config, sink)?;
+    let out: &mut dyn Write = sink.geometry();
+
+    writeln!(out, "// everting sphere, {:?} at t = {}", grid.oper, grid.t)?;
+    writeln!(out, "polyhedron(")?;
+    writeln!(out, "  points = [")?;
+    for p in welded.positions.iter() {
+        writeln!(out, "    [{:.6}, {:.6}, {:.6}],", p[0], p[1], p[2])?;
+    };
+    writeln!(out, "  ],")?;
+    writeln!(out, "  faces = [")?;
+    for [a, b, c] in welded.faces.iter() {
+        writeln!(out, "    [{a}, {c}, {b}],")?;
+    };
+    writeln!(out, "  ],")?;
+    writeln!(out, "  convexity = 10")?;
+    writeln!(out, ");")?;
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn clockwise_faces_and_a_warning() {
+        use crate::{eversion::{Eversion, Grid}, nstrip::EversionConfig, sink::Sink, transform::Transform};
+        let eversion: Eversion = Eversion::default();
+        // large enough to print, so that only the shape is warned about
+        let config: EversionConfig = EversionConfig { transform: Transform::scale(10.0), ..Default::default() };
+        let polyhedron = |grid: &Grid| -> (String, String) {
+            let (scad, warnings): (Vec<u8>, String) = crate::export::tests::written(|sink: &mut dyn Sink| super::print_scad(grid, &config, sink)).unwrap();
+            return (String::from_utf8(scad).unwrap(), warnings);
+        };
+        let (scad, warnings): (String, String) = polyhedron(&eversion.grid_at(0.0).unwrap());
+        assert_eq!(warnings, "");
+        let points: Vec<[f64; 3]> = scad.lines().filter(|line: &&str| line.matches(',').count() == 3 && line.contains('.'))
+            .map(|line: &str| {
+                let xyz: Vec<f64> = line.trim().trim_start_matches('[').trim_end_matches("],").split(", ").map(|x: &str| x.parse().unwrap()).collect();
+                [xyz[0], xyz[1], xyz[2]]
+            })
+            .collect();
+        let faces: Vec<[usize; 3]> = scad[scad.find("faces").unwrap()..].lines().filter(|line: &&str| line.trim().starts_with('['))
+            .map(|line: &str| {
+                let abc: Vec<usize> = line.trim().trim_start_matches('[').trim_end_matches("],").split(", ").map(|x: &str| x.parse().unwrap()).collect();
+                [abc[0], abc[1], abc[2]]
+            })
+            .collect();
+        // clockwise seen from outside: negative signed volume
+        let volume: f64 = faces.iter().map(|[a, b, c]: &[usize; 3]| crate::export::dot(points[*a], crate::export::cross(points[*b], points[*c]))).sum();
+        assert!(volume < 0.0, "{volume}");
+        let grid: Grid = eversion.grid_at(0.5).unwrap();
+        let (_, warnings): (String, String) = polyhedron(&grid);
+        assert_eq!(warnings.lines().collect::<Vec<&str>>(), vec![
+            format!("warning: the sphere, {:?} at t = {}, intersects itself; slicers may not print it as intended", grid.oper, grid.t),
+        ]);
+    }
+}
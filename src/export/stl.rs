@@ -0,0 +1,119 @@
+use std::io::Write;
+
+use crate::{
+    eversion::Grid,
+    export::{cross, dot, facet_normal, pieces, Piece},
+    nstrip::EversionConfig,
+    sink::Sink,
+};
+
+/// Writes `grid` as STL, ASCII or, with `config.binary`, binary, one solid for all selected parts.
+///
+/// Facets wind consistently with the jet normals, all turned outwards, to enclose positive volume,
+/// where the jet normals point inwards, as they do at T = 0. The zero-area halves of the quads at
+/// the poles are dropped.
+pub fn print_stl(grid: &Grid, parts: Vec<char>, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let pieces: Vec<Piece> = pieces(grid, parts, config)?;
+    let mut facets: Vec<[[f64; 3]; 4]> = pieces.iter().flat_map(|piece: &Piece| {
+        piece.triangles().into_iter().map(|triangle: [usize; 3]| {
+            let [p, q, r] = triangle.map(|i: usize| piece.vertices[i].position);
+            [facet_normal(p, q, r), p, q, r]
+        }).collect::<Vec<[[f64; 3]; 4]>>()
+    }).collect();
+    if facets.iter().map(|[_, p, q, r]: &[[f64; 3]; 4]| dot(*p, cross(*q, *r))).sum::<f64>() < 0.0 {
+        for facet in facets.iter_mut() {
+            facet.swap(2, 3);
+            facet[0] = facet[0].map(|x: f64| -x);
+        };
+    };
+    let name: String = format!("evert {:?} t={}", grid.oper, grid.t);
+    let out: &mut dyn Write = sink.geometry();
+
+    if config.binary {
+        let mut header: [u8; 80] = [b' '; 80];
+        header[..name.len().min(80)].copy_from_slice(&name.as_bytes()[..name.len().min(80)]);
+        out.write_all(&header)?;
+        out.write_all(&(facets.len() as u32).to_le_bytes())?;
+        for facet in facets.iter() {
+            for c in facet.iter().flatten() { out.write_all(&(*c as f32).to_le_bytes())?; };
+            out.write_all(&0u16.to_le_bytes())?;
+        };
+    } else {
+        writeln!(out, "solid {name}")?;
+        for [[nx, ny, nz], p, q, r] in facets.iter() {
+            writeln!(out, "  facet normal {nx:e} {ny:e} {nz:e}")?;
+            writeln!(out, "    outer loop")?;
+            for [x, y, z] in [p, q, r] {
+                writeln!(out, "      vertex {x:e} {y:e} {z:e}")?;
+            };
+            writeln!(out, "    endloop")?;
+            writeln!(out, "  endfacet")?;
+        };
+        writeln!(out, "endsolid {name}")?;
+    };
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{export::tests::{text, written}, sink::Sink};
+
+    #[test]
+    fn binary_pole_to_pole() {
+        let config: crate::nstrip::EversionConfig = crate::nstrip::EversionConfig::new(8, true, false);
+        let eversion: crate::Eversion = crate::Eversion { config, sampling: crate::Sampling { umax: 2.0, ..Default::default() }, ..Default::default() };
+        let grid: crate::Grid = eversion.grid(crate::Sto::Twist, 0.5);
+
+        let (data, _): (Vec<u8>, String) = written(|sink: &mut dyn Sink| super::print_stl(&grid, vec!['+', '0'], &config, sink)).unwrap();
+
+        let count: usize = u32::from_le_bytes(data[80..84].try_into().unwrap()) as usize;
+        assert_eq!(data.len(), 84 + count * 50);
+        // both poles leave one triangle of each of their quads behind
+        assert_eq!(count, 2 * grid.jmax * grid.kmax - 2 * grid.kmax);
+
+        for facet in data[84..].chunks_exact(50) {
+            let f: Vec<f64> = facet[..48].chunks_exact(4).map(|b: &[u8]| f32::from_le_bytes(b.try_into().unwrap()) as f64).collect();
+            let n: [f64; 3] = [f[0], f[1], f[2]];
+            assert!((crate::export::dot(n, n) - 1.0).abs() < 1e-5, "facet normal {n:?}");
+            let winding: [f64; 3] = crate::export::cross(crate::export::sub([f[6], f[7], f[8]], [f[3], f[4], f[5]]), crate::export::sub([f[9], f[10], f[11]], [f[3], f[4], f[5]]));
+            assert!(crate::export::dot(winding, n) > 0.0, "facet normal disagrees with the winding");
+        };
+    }
+    #[test]
+    fn outward_at_the_start() {
+        let eversion: crate::Eversion = crate::Eversion::default();
+        let grid: crate::Grid = eversion.grid_at(0.0).unwrap();
+        let config: crate::nstrip::EversionConfig = crate::nstrip::EversionConfig { binary: true, ..Default::default() };
+        let (data, _): (Vec<u8>, String) = written(|sink: &mut dyn Sink| super::print_stl(&grid, vec!['*'], &config, sink)).unwrap();
+
+        let mut volume: f64 = 0.0;
+        for facet in data[84..].chunks_exact(50) {
+            let f: Vec<f64> = facet[..48].chunks_exact(4).map(|b: &[u8]| f32::from_le_bytes(b.try_into().unwrap()) as f64).collect();
+            let (n, p, q, r): ([f64; 3], [f64; 3], [f64; 3], [f64; 3]) = ([f[0], f[1], f[2]], [f[3], f[4], f[5]], [f[6], f[7], f[8]], [f[9], f[10], f[11]]);
+            // the unit sphere is centred on the origin, so outwards is away from it
+            let centroid: [f64; 3] = [0, 1, 2].map(|d: usize| (p[d] + q[d] + r[d]) / 3.0);
+            assert!(crate::export::dot(n, centroid) > 0.0, "facet normal {n:?} at {centroid:?} points inwards");
+            volume += crate::export::dot(p, crate::export::cross(q, r)) / 6.0;
+        };
+        assert!((volume - 4.0 / 3.0 * std::f64::consts::PI).abs() < 0.05, "encloses {volume}");
+    }
+    #[test]
+    fn ascii_at_any_scale() {
+        let grid: crate::Grid = crate::Eversion::default().grid(crate::Sto::PushThrough, 0.5);
+        let stl = |scale: f64| -> String {
+            let config: crate::nstrip::EversionConfig = crate::nstrip::EversionConfig { transform: crate::Transform::scale(scale), ..Default::default() };
+            return text(|sink: &mut dyn Sink| super::print_stl(&grid, vec!['+', '0', '-', '0'], &config, sink));
+        };
+        let count = |stl: &str, prefix: &str| -> usize { stl.lines().filter(|line: &&str| line.trim_start().starts_with(prefix)).count() };
+        let facets: usize = 2 * (2 * grid.jmax * grid.kmax - grid.kmax);
+        for scale in [1e-13, 1.0, 1e4] {
+            let ascii: String = stl(scale);
+            let lines: Vec<&str> = ascii.lines().collect();
+            assert!(lines[0].starts_with("solid evert PushThrough"), "{}", lines[0]);
+            assert_eq!(lines[lines.len() - 1], lines[0].replacen("solid", "endsolid", 1));
+            assert_eq!(count(&ascii, "solid "), 1);
+            // the pole quads lose their degenerate half at every scale
+            assert_eq!((count(&ascii, "facet normal "), count(&ascii, "vertex "), count(&ascii, "endfacet")), (facets, 3 * facets, facets), "at scale {scale}");
+        };
+    }
+}
@@ -0,0 +1,88 @@
+use std::io::Write;
+
+use crate::{
+    eversion::Grid,
+    export::{off::{solid, Welded}, zip},
+    nstrip::EversionConfig,
+    sink::Sink,
+};
+
+/// The 3MF core model of `welded`, one mesh object built once.
+fn model(grid: &Grid, welded: &Welded) -> String {
+    let mut xml: String = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<model unit=\"millimeter\" xml:lang=\"en-US\" xmlns=\"http://schemas.microsoft.com/3dmanufacturing/core/2015/02\">\n");
+    xml.push_str(&format!("  <metadata name=\"Title\">everting sphere, {:?} at t = {}</metadata>\n", grid.oper, grid.t));
+    xml.push_str("  <resources>\n    <object id=\"1\" type=\"model\">\n      <mesh>\n        <vertices>\n");
+    for p in welded.positions.iter() {
+        xml.push_str(&format!("          <vertex x=\"{:.6}\" y=\"{:.6}\" z=\"{:.6}\"/>\n", p[0], p[1], p[2]));
+    };
+    xml.push_str("        </vertices>\n        <triangles>\n");
+    for [a, b, c] in welded.faces.iter() {
+        xml.push_str(&format!("          <triangle v1=\"{a}\" v2=\"{b}\" v3=\"{c}\"/>\n"));
+    };
+    xml.push_str("        </triangles>\n      </mesh>\n    </object>\n  </resources>\n");
+    xml.push_str("  <build>\n    <item objectid=\"1\"/>\n  </build>\n</model>\n");
+    return xml;
+}
+
+/// Writes the whole sphere at `grid`, welded, as a 3MF package: one mesh object, its triangles
+/// counter-clockwise seen from outside as [`solid`] winds them. Units are millimetres; see `--scale`.
+pub fn print_3mf(grid: &Grid, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let welded: Welded = solid(grid, config, sink)?;
+    let content_types: &str = concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\n",
+        "  <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\n",
+        "  <Default Extension=\"model\" ContentType=\"application/vnd.ms-package.3dmanufacturing-3dmodel+xml\"/>\n",
+        "</Types>\n",
+    );
+    let relationships: &str = concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n",
+        "  <Relationship Target=\"/3D/3dmodel.model\" Id=\"rel0\" Type=\"http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel\"/>\n",
+        "</Relationships>\n",
+    );
+    let package: Vec<u8> = zip(&[
+        ("[Content_Types].xml", content_types.as_bytes().to_vec()),
+        ("_rels/.rels", relationships.as_bytes().to_vec()),
+        ("3D/3dmodel.model", model(grid, &welded).into_bytes()),
+    ]);
+    let out: &mut dyn Write = sink.geometry();
+    out.write_all(&package)?;
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn stored_package() {
+        use crate::{eversion::{Eversion, Grid}, nstrip::EversionConfig, sink::Sink, transform::Transform};
+        assert_eq!(crate::export::crc32(b"123456789"), 0xCBF4_3926);
+        let grid: Grid = Eversion::default().grid_at(1.0).unwrap();
+        let config: EversionConfig = EversionConfig { transform: Transform::scale(10.0), ..Default::default() };
+        let (package, warnings): (Vec<u8>, String) = crate::export::tests::written(|sink: &mut dyn Sink| super::print_3mf(&grid, &config, sink)).unwrap();
+        assert_eq!(warnings, "");
+
+        // walk the local headers, checking every member against its CRC
+        let u16_at = |i: usize| -> usize { u16::from_le_bytes([package[i], package[i + 1]]) as usize };
+        let u32_at = |i: usize| -> u32 { u32::from_le_bytes([package[i], package[i + 1], package[i + 2], package[i + 3]]) };
+        let (mut at, mut names): (usize, Vec<String>) = (0, Vec::new());
+        while u32_at(at) == 0x0403_4b50 {
+            let (size, name_length): (usize, usize) = (u32_at(at + 18) as usize, u16_at(at + 26));
+            let name: String = String::from_utf8(package[at + 30..at + 30 + name_length].to_vec()).unwrap();
+            let data: &[u8] = &package[at + 30 + name_length..at + 30 + name_length + size];
+            assert_eq!(crate::export::crc32(data), u32_at(at + 14), "{name}");
+            if name == "3D/3dmodel.model" {
+                let model: &str = std::str::from_utf8(data).unwrap();
+                assert!(model.matches("<triangle ").count() > 0);
+                assert!(model.ends_with("</model>\n"));
+            };
+            names.push(name);
+            at += 30 + name_length + size;
+        };
+        assert_eq!(names, vec!["[Content_Types].xml", "_rels/.rels", "3D/3dmodel.model"]);
+        assert_eq!(u32_at(package.len() - 22), 0x0605_4b50);
+        assert_eq!(u16_at(package.len() - 12), 3);
+    }
+}
@@ -0,0 +1,163 @@
+use std::io::Write;
+
+use crate::{
+    eversion::{Eversion, Grid},
+    export::{control_net, pieces, sweep, Piece, Vertex},
+    sink::Sink,
+};
+
+fn tuples<const N: usize>(values: impl IntoIterator<Item = [f64; N]>) -> String {
+    let tuples: Vec<String> = values.into_iter()
+        .map(|value: [f64; N]| format!("({})", value.map(|x: f64| format!("{x:.6}")).join(", ")))
+        .collect();
+    return format!("[{}]", tuples.join(", "));
+}
+
+fn list<T: std::fmt::Display>(values: impl IntoIterator<Item = T>) -> String {
+    return format!("[{}]", values.into_iter().map(|x: T| x.to_string()).collect::<Vec<String>>().join(", "));
+}
+
+/// `timeSamples` of the attribute `declaration`, one line per time code.
+fn print_samples(out: &mut dyn Write, indent: &str, declaration: &str, samples: &[(usize, String)]) -> std::io::Result<()> {
+    writeln!(out, "{indent}{declaration}.timeSamples = {{")?;
+    for (code, value) in samples {
+        writeln!(out, "{indent}    {code}: {value},")?;
+    };
+    return writeln!(out, "{indent}}}");
+}
+
+/// Bounds of `points` as a USD `extent`.
+fn extent(points: &[[f64; 3]]) -> String {
+    let (mut min, mut max): ([f64; 3], [f64; 3]) = ([f64::MAX; 3], [f64::MIN; 3]);
+    for p in points {
+        for d in 0..3 { min[d] = min[d].min(p[d]); max[d] = max[d].max(p[d]); };
+    };
+    return tuples([min, max]);
+}
+
+/// Knots of a Bezier spline of `samples.len() - 1` cubic spans as an order 4 NURBS: every sample
+/// is a knot of multiplicity 3, the ends of multiplicity 4.
+fn bezier_knots(samples: &[f64]) -> Vec<f64> {
+    let mut knots: Vec<f64> = vec![samples[0]];
+    for x in samples { knots.extend([*x; 3]); };
+    knots.push(samples[samples.len() - 1]);
+    return knots;
+}
+
+/// USD prim name of a piece: `unit`, `p3` for `+3`, `m3` for `-3`.
+fn prim_name(piece: &Piece) -> String {
+    return piece.name.replace('+', "p").replace('-', "m");
+}
+
+/// Writes the whole eversion as a USD ASCII layer over time codes `0..frames - 1`, spread over
+/// `duration` seconds.
+///
+/// One `Mesh` prim holds every selected part, its quads authored once and its `points`,
+/// `normals` and `extent` as `timeSamples`; `primvars:st` is (u, v). With
+/// `eversion.config.bezier` each part is a `NurbsPatch` instead, the Bezier control net of
+/// `print_spline` as a bicubic NURBS with time-sampled `points`.
+pub fn print_usda(eversion: &Eversion, frames: usize, duration: f64, parts: Vec<char>, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let samples: Vec<(f64, Grid)> = sweep(eversion, frames)?;
+    let first: &Grid = &samples[0].1;
+    let last: usize = frames - 1;
+    let code = |time: f64| -> usize { (time * last as f64).round() as usize };
+    let sampled: Vec<(usize, Vec<Piece>)> = samples.iter()
+        .map(|(time, grid): &(f64, Grid)| pieces(grid, parts.clone(), &eversion.config).map(|p: Vec<Piece>| (code(*time), p)))
+        .collect::<std::io::Result<_>>()?;
+    let layout: &Vec<Piece> = &sampled[0].1;
+    let out: &mut dyn Write = sink.geometry();
+
+    writeln!(out, "#usda 1.0")?;
+    writeln!(out, "(")?;
+    writeln!(out, "    doc = \"everting sphere, {} frames of T = 0..1\"", samples.len())?;
+    writeln!(out, "    defaultPrim = \"Evert\"")?;
+    writeln!(out, "    upAxis = \"Z\"")?;
+    writeln!(out, "    startTimeCode = 0")?;
+    writeln!(out, "    endTimeCode = {last}")?;
+    writeln!(out, "    timeCodesPerSecond = {}", last as f64 / duration)?;
+    writeln!(out, ")")?;
+    writeln!(out)?;
+    writeln!(out, "def Xform \"Evert\"")?;
+    writeln!(out, "{{")?;
+    if eversion.config.bezier {
+        for (i, piece) in layout.iter().enumerate() {
+            let (nu, nv): (usize, usize) = (3 * piece.grid.jmax + 1, 3 * piece.grid.kmax + 1);
+            let (us, vs): (Vec<f64>, Vec<f64>) = ((0..=first.jmax).map(|j: usize| first.u(j)).collect(), (0..=first.kmax).map(|k: usize| first.v(k)).collect());
+            writeln!(out, "    def NurbsPatch \"{}\"", prim_name(piece))?;
+            writeln!(out, "    {{")?;
+            writeln!(out, "        int uVertexCount = {nu}")?;
+            writeln!(out, "        int vVertexCount = {nv}")?;
+            writeln!(out, "        int uOrder = 4")?;
+            writeln!(out, "        int vOrder = 4")?;
+            writeln!(out, "        double[] uKnots = {}", list(bezier_knots(&us)))?;
+            writeln!(out, "        double[] vKnots = {}", list(bezier_knots(&vs)))?;
+            writeln!(out, "        double2 uRange = ({}, {})", us[0], us[us.len() - 1])?;
+            writeln!(out, "        double2 vRange = ({}, {})", vs[0], vs[vs.len() - 1])?;
+            let nets: Vec<(usize, Vec<[f64; 3]>)> = sampled.iter().map(|(code, pieces): &(usize, Vec<Piece>)| (*code, control_net(&pieces[i].grid))).collect();
+            print_samples(out, "        ", "float3[] extent", &nets.iter().map(|(code, net): &(usize, Vec<[f64; 3]>)| (*code, extent(net))).collect::<Vec<_>>())?;
+            print_samples(out, "        ", "point3f[] points", &nets.into_iter().map(|(code, net): (usize, Vec<[f64; 3]>)| (code, tuples(net))).collect::<Vec<_>>())?;
+            writeln!(out, "    }}")?;
+        };
+    } else {
+        let mut indices: Vec<usize> = Vec::new();
+        let mut base: usize = 0;
+        for piece in layout.iter() {
+            indices.extend(piece.quads().into_iter().flatten().map(|i: usize| i + base));
+            base += piece.vertices.len();
+        };
+        let points: Vec<(usize, Vec<[f64; 3]>)> = sampled.iter()
+            .map(|(code, pieces): &(usize, Vec<Piece>)| (*code, pieces.iter().flat_map(|piece: &Piece| piece.vertices.iter().map(|v: &Vertex| v.position)).collect()))
+            .collect();
+        writeln!(out, "    def Mesh \"Sphere\"")?;
+        writeln!(out, "    {{")?;
+        writeln!(out, "        uniform token subdivisionScheme = \"none\"")?;
+        writeln!(out, "        uniform token orientation = \"rightHanded\"")?;
+        writeln!(out, "        int[] faceVertexCounts = {}", list(vec![4; indices.len() / 4]))?;
+        writeln!(out, "        int[] faceVertexIndices = {}", list(indices))?;
+        writeln!(out, "        texCoord2f[] primvars:st = {} (", tuples(layout.iter().flat_map(|piece: &Piece| piece.vertices.iter().map(|v: &Vertex| v.uv))))?;
+        writeln!(out, "            interpolation = \"vertex\"")?;
+        writeln!(out, "        )")?;
+        print_samples(out, "        ", "float3[] extent", &points.iter().map(|(code, p): &(usize, Vec<[f64; 3]>)| (*code, extent(p))).collect::<Vec<_>>())?;
+        print_samples(out, "        ", "point3f[] points", &points.into_iter().map(|(code, p): (usize, Vec<[f64; 3]>)| (code, tuples(p))).collect::<Vec<_>>())?;
+        let normals: Vec<(usize, String)> = sampled.iter()
+            .map(|(code, pieces): &(usize, Vec<Piece>)| (*code, tuples(pieces.iter().flat_map(|piece: &Piece| piece.shading_normals()))))
+            .collect();
+        print_samples(out, "        ", "normal3f[] normals", &normals)?;
+        writeln!(out, "    }}")?;
+    };
+    writeln!(out, "}}")?;
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    fn layer(config: crate::nstrip::EversionConfig) -> String {
+        let eversion: crate::Eversion = crate::Eversion { config, ..Default::default() };
+        return crate::export::tests::text(|sink: &mut dyn crate::sink::Sink| super::print_usda(&eversion, 5, 2.0, vec!['+', '0', '-', '0'], sink));
+    }
+    /// Values of every `name = value` line, in order.
+    fn values<'a>(usda: &'a str, name: &str) -> Vec<&'a str> {
+        return usda.lines().filter_map(|line: &str| line.trim().strip_prefix(name)?.trim_start().strip_prefix('=')).map(str::trim).collect();
+    }
+    #[test]
+    fn mesh_topology_once_points_sampled() {
+        let usda: String = layer(crate::nstrip::EversionConfig::default());
+        assert!(usda.starts_with("#usda 1.0\n"));
+        assert_eq!(usda.matches("faceVertexIndices").count(), 1);
+        assert_eq!(values(&usda, "timeCodesPerSecond"), vec!["2"]);
+        assert_eq!((values(&usda, "startTimeCode"), values(&usda, "endTimeCode")), (vec!["0"], vec!["4"]));
+        let points: &str = &usda[usda.find("point3f[] points.timeSamples").unwrap()..usda.find("normal3f[] normals.timeSamples").unwrap()];
+        assert_eq!(points.trim_end().lines().count(), 1 + 5 + 1);
+        // two 13 x 13 pieces at every time code
+        assert!(points.lines().skip(1).take(5).all(|line: &str| line.matches('(').count() == 2 * 13 * 13), "{points}");
+    }
+    #[test]
+    fn nurbs_patches() {
+        let usda: String = layer(crate::nstrip::EversionConfig { bezier: true, ..Default::default() });
+        assert_eq!(usda.matches("def NurbsPatch").count(), 2);
+        assert_eq!(values(&usda, "int uVertexCount"), vec!["37", "37"]);
+        // vertex count + order
+        let knots: &str = usda.lines().find(|line: &&str| line.contains("uKnots")).unwrap();
+        assert_eq!(knots.matches(',').count() + 1, 37 + 4);
+    }
+}
@@ -0,0 +1,132 @@
+use std::io::Write;
+
+use crate::{
+    eversion::{calc_speed_u, Grid},
+    export::{off::latitude_color, replicas, Piece, Replicas},
+    nstrip::EversionConfig,
+    sink::Sink,
+    spline::print_part_side,
+    transform::Transform,
+    twojetvec::TwoJetVec,
+};
+
+/// Green at v = 0 through magenta at v = 1, apart from the latitude colors of the u lines.
+fn longitude_color(v: f64) -> [f64; 4] {
+    let s: f64 = v.clamp(0.0, 1.0);
+    return [0.2 + 0.7 * s, 0.8 - 0.6 * s, 0.3 + 0.5 * s, 1.0];
+}
+
+/// The u = const lines at every `grid.u(j)`, then the v = const lines at every `grid.v(k)`, each
+/// evaluated `subdivisions` times per grid cell along its length, with its color.
+fn parameter_lines(grid: &Grid, config: &EversionConfig, subdivisions: usize, transform: &Transform) -> Vec<(Vec<[f64; 3]>, [f64; 4])> {
+    let s: usize = subdivisions.max(1);
+    let point = |u: f64, v: f64| -> [f64; 3] {
+        let p: TwoJetVec = calc_speed_u(grid.oper, u, v, grid.t, config);
+        transform.apply_point([p.x().f(), p.y().f(), p.z().f()])
+    };
+    let mut lines: Vec<(Vec<[f64; 3]>, [f64; 4])> = Vec::with_capacity(grid.jmax + grid.kmax + 2);
+    for j in 0..=grid.jmax {
+        let u: f64 = grid.u(j);
+        lines.push(((0..=grid.kmax * s).map(|i: usize| point(u, grid.vmin + grid.dv * i as f64 / s as f64)).collect(), latitude_color(u)));
+    };
+    for k in 0..=grid.kmax {
+        let v: f64 = grid.v(k);
+        lines.push(((0..=grid.jmax * s).map(|i: usize| point(grid.umin + grid.du * i as f64 / s as f64, v)).collect(), longitude_color(v)));
+    };
+    return lines;
+}
+
+fn print_vect(out: &mut dyn Write, lines: &[(Vec<[f64; 3]>, [f64; 4])]) -> std::io::Result<()> {
+    let vertices: usize = lines.iter().map(|(points, _): &(Vec<[f64; 3]>, [f64; 4])| points.len()).sum();
+    writeln!(out, "{{ VECT")?;
+    writeln!(out, "{} {} {}", lines.len(), vertices, lines.len())?;
+    writeln!(out, "{}", lines.iter().map(|(points, _): &(Vec<[f64; 3]>, [f64; 4])| points.len().to_string()).collect::<Vec<String>>().join(" "))?;
+    writeln!(out, "{}", vec!["1"; lines.len()].join(" "))?;
+    for (points, _) in lines {
+        for p in points { writeln!(out, "{:.6} {:.6} {:.6}", p[0], p[1], p[2])?; };
+    };
+    for (_, c) in lines {
+        writeln!(out, "{:.3} {:.3} {:.3} {:.3}", c[0], c[1], c[2], c[3])?;
+    };
+    return writeln!(out, "}}");
+}
+
+/// Writes the u = const and v = const lines through the samples of `grid` as an OOGL `VECT`,
+/// one color per line, evaluated `subdivisions` times per grid cell, more finely than the
+/// mesh. The u lines are colored by latitude as in OFF output, the v lines by v.
+///
+/// Replicated over `parts` like [`print_scene`](crate::spline::print_scene): an `INST` over
+/// a `TLIST`, or with `config.baked` a `LIST` of copies.
+pub fn print_lines(grid: &Grid, parts: Vec<char>, config: &EversionConfig, subdivisions: usize, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let out: &mut dyn Write = sink.geometry();
+    match replicas(grid, parts, config)? {
+        Replicas::Baked(pieces) => {
+            // the unit alone stands without a LIST; every line is evaluated afresh under the placement
+            let list: bool = pieces.iter().any(|piece: &Piece| piece.part.is_some());
+            if list { writeln!(out, "{{ LIST")?; };
+            for piece in pieces.iter() {
+                if list { writeln!(out, "# {} of {}", piece.name, config.n_strips)?; };
+                print_vect(out, &parameter_lines(grid, config, subdivisions, &piece.transform(config)))?;
+            };
+            if list { writeln!(out, "}}")?; };
+        },
+        Replicas::Instanced(parts) => {
+            writeln!(out, "{{ INST transforms {{ TLIST")?;
+            print_part_side(out, &parts, config.n_strips, &config.transform)?;
+            write!(out, "}}\ngeom ")?;
+            print_vect(out, &parameter_lines(grid, config, subdivisions, &Transform::identity()))?;
+            writeln!(out, "}}")?;
+        },
+    };
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn lines_through_the_grid() {
+        use crate::{eversion::{Eversion, Grid}, nstrip::EversionConfig, sink::Sink};
+        let grid: Grid = Eversion::default().grid(crate::Sto::PushThrough, 0.6);
+        let config: EversionConfig = EversionConfig::default();
+        let vect: String = crate::export::tests::text(|sink: &mut dyn Sink| super::print_lines(&grid, vec![], &config, 4, sink));
+
+        let lines: Vec<&str> = vect.lines().collect();
+        assert_eq!(lines[0], "{ VECT");
+        let counts: Vec<usize> = lines[1].split(' ').map(|n: &str| n.parse().unwrap()).collect();
+        let (nu, nv): (usize, usize) = (grid.jmax + 1, grid.kmax + 1);
+        assert_eq!(counts, vec![nu + nv, nu * (4 * grid.kmax + 1) + nv * (4 * grid.jmax + 1), nu + nv]);
+        assert!(lines[3].split(' ').all(|n: &str| n == "1"));
+        // every fourth vertex of the u = u(2) line is a grid sample
+        let first: usize = 4 + 2 * (4 * grid.kmax + 1);
+        for k in [0, 5, grid.kmax] {
+            let p: Vec<f64> = lines[first + 4 * k].split(' ').map(|x: &str| x.parse().unwrap()).collect();
+            let f: [f64; 3] = [grid.values[2][k].x().f(), grid.values[2][k].y().f(), grid.values[2][k].z().f()];
+            for d in 0..3 { assert!((p[d] - f[d]).abs() < 1e-5, "k = {k}"); };
+        };
+        assert_eq!(lines.len(), 4 + counts[1] + counts[2] + 1);
+    }
+    #[test]
+    fn replicated_by_tlist() {
+        use crate::{eversion::{Eversion, Grid}, nstrip::EversionConfig, sink::Sink};
+        let grid: Grid = Eversion::default().grid(crate::Sto::Twist, 0.2);
+        let vect = |config: &EversionConfig| -> Vec<String> {
+            let text: String = crate::export::tests::text(|sink: &mut dyn Sink| super::print_lines(&grid, vec!['+', '1', '-', '1'], config, 2, sink));
+            return text.lines().map(String::from).collect();
+        };
+        let headers = |lines: &Vec<String>| -> usize { lines.iter().filter(|line: &&String| line.ends_with("{ VECT")).count() };
+        let depth = |lines: &Vec<String>| -> i64 {
+            lines.iter().map(|line: &String| line.matches('{').count() as i64 - line.matches('}').count() as i64).sum()
+        };
+        let instanced: Vec<String> = vect(&EversionConfig::default());
+        assert_eq!(instanced[0], "{ INST transforms { TLIST");
+        // one 4x4 matrix per part, then the lines once
+        let matrices: usize = instanced.iter().take_while(|line: &&String| !line.ends_with("{ VECT")).filter(|line: &&String| !line.starts_with('#') && line.split_whitespace().count() == 4).count();
+        assert_eq!(matrices, 2 * 4);
+        assert_eq!(headers(&instanced), 1);
+        assert_eq!(depth(&instanced), 0);
+        let baked: Vec<String> = vect(&EversionConfig { baked: true, ..Default::default() });
+        assert_eq!(baked[0], "{ LIST");
+        assert_eq!(headers(&baked), 2);
+        assert_eq!(depth(&baked), 0);
+    }
+}
@@ -0,0 +1,195 @@
+use std::io::Write;
+
+use crate::{
+    eversion::{Eversion, Grid},
+    export::{base64, pieces, sweep, Piece, Vertex},
+    nstrip::EversionConfig,
+    sink::{Sink, WriterSink},
+};
+
+/// Contents of one `DataArray`.
+enum Values {
+    Float32(Vec<f32>),
+    Int32(Vec<i32>),
+    UInt8(Vec<u8>),
+}
+
+impl Values {
+    fn name(&self) -> &'static str {
+        match self { Values::Float32(_) => "Float32", Values::Int32(_) => "Int32", Values::UInt8(_) => "UInt8" }
+    }
+    fn text(&self) -> String {
+        match self {
+            Values::Float32(v) => v.iter().map(|x: &f32| x.to_string()).collect::<Vec<String>>().join(" "),
+            Values::Int32(v)   => v.iter().map(|x: &i32| x.to_string()).collect::<Vec<String>>().join(" "),
+            Values::UInt8(v)   => v.iter().map(|x: &u8| x.to_string()).collect::<Vec<String>>().join(" "),
+        }
+    }
+    /// Inline binary payload: the UInt32 byte count, then the little-endian values, all in base64.
+    fn base64(&self) -> String {
+        let mut bytes: Vec<u8> = match self {
+            Values::Float32(v) => v.iter().flat_map(|x: &f32| x.to_le_bytes()).collect(),
+            Values::Int32(v)   => v.iter().flat_map(|x: &i32| x.to_le_bytes()).collect(),
+            Values::UInt8(v)   => v.clone(),
+        };
+        bytes.splice(0..0, (bytes.len() as u32).to_le_bytes());
+        return base64(&bytes);
+    }
+}
+
+fn print_array(out: &mut dyn Write, name: &str, components: usize, values: Values, binary: bool) -> std::io::Result<()> {
+    writeln!(out, r#"        <DataArray type="{}" Name="{name}" NumberOfComponents="{components}" format="{}">"#, values.name(), if binary { "binary" } else { "ascii" })?;
+    writeln!(out, "          {}", if binary { values.base64() } else { values.text() })?;
+    writeln!(out, "        </DataArray>")?;
+    return Ok(());
+}
+
+/// Writes `grid`, sampled at global `time`, as a VTK XML unstructured grid (`.vtu`) of quads;
+/// inline base64 binary with `config.binary`, ASCII otherwise.
+///
+/// Point data: the shading `Normals`, `uv`, `speedu`, `speedv`, the `strip` each point was replicated to and its
+/// `hemisphere` (+1 for `+k`, -1 for `-k`, 0 without replication), and `time`; the field data carries `TimeValue` as well, for ParaView.
+pub fn print_vtu(grid: &Grid, time: f64, parts: Vec<char>, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let pieces: Vec<Piece> = pieces(grid, parts, config)?;
+    let vertices: Vec<&Vertex> = pieces.iter().flat_map(|piece: &Piece| piece.vertices.iter()).collect();
+    let strips: Vec<i32> = pieces.iter()
+        .flat_map(|piece: &Piece| std::iter::repeat_n(piece.part.as_ref().map_or(0, |part| part.strip as i32), piece.vertices.len()))
+        .collect();
+    let hemispheres: Vec<i32> = pieces.iter()
+        .flat_map(|piece: &Piece| std::iter::repeat_n(piece.part.as_ref().map_or(0, |part| if part.sign == '-' { -1 } else { 1 }), piece.vertices.len()))
+        .collect();
+    let mut cells: Vec<i32> = Vec::new();
+    let mut base: usize = 0;
+    for piece in pieces.iter() {
+        cells.extend(piece.quads().into_iter().flatten().map(|i: usize| (i + base) as i32));
+        base += piece.vertices.len();
+    };
+    let normals: Vec<[f64; 3]> = pieces.iter().flat_map(|piece: &Piece| piece.shading_normals()).collect();
+    let (points, quads): (usize, usize) = (vertices.len(), cells.len() / 4);
+    let out: &mut dyn Write = sink.geometry();
+
+    writeln!(out, r#"<?xml version="1.0"?>"#)?;
+    writeln!(out, r#"<VTKFile type="UnstructuredGrid" version="0.1" byte_order="LittleEndian" header_type="UInt32">"#)?;
+    writeln!(out, "  <!-- everting sphere, {:?} at t = {} -->", grid.oper, grid.t)?;
+    writeln!(out, "  <UnstructuredGrid>")?;
+    writeln!(out, "    <FieldData>")?;
+    writeln!(out, r#"      <DataArray type="Float64" Name="TimeValue" NumberOfTuples="1" format="ascii">{time}</DataArray>"#)?;
+    writeln!(out, "    </FieldData>")?;
+    writeln!(out, r#"    <Piece NumberOfPoints="{points}" NumberOfCells="{quads}">"#)?;
+    writeln!(out, "      <Points>")?;
+    print_array(out, "Points", 3, Values::Float32(vertices.iter().flat_map(|v: &&Vertex| v.position.map(|x: f64| x as f32)).collect()), config.binary)?;
+    writeln!(out, "      </Points>")?;
+    writeln!(out, r#"      <Cells>"#)?;
+    print_array(out, "connectivity", 1, Values::Int32(cells), config.binary)?;
+    print_array(out, "offsets", 1, Values::Int32((1..=quads).map(|i: usize| (4 * i) as i32).collect()), config.binary)?;
+    // VTK_QUAD
+    print_array(out, "types", 1, Values::UInt8(vec![9; quads]), config.binary)?;
+    writeln!(out, "      </Cells>")?;
+    writeln!(out, r#"      <PointData Normals="Normals" TCoords="uv" Scalars="speedu">"#)?;
+    print_array(out, "Normals", 3, Values::Float32(normals.iter().flat_map(|n: &[f64; 3]| n.map(|x: f64| x as f32)).collect()), config.binary)?;
+    print_array(out, "uv", 2, Values::Float32(vertices.iter().flat_map(|v: &&Vertex| v.uv.map(|x: f64| x as f32)).collect()), config.binary)?;
+    print_array(out, "speedu", 1, Values::Float32(vertices.iter().map(|v: &&Vertex| v.speed[0] as f32).collect()), config.binary)?;
+    print_array(out, "speedv", 1, Values::Float32(vertices.iter().map(|v: &&Vertex| v.speed[1] as f32).collect()), config.binary)?;
+    print_array(out, "strip", 1, Values::Int32(strips), config.binary)?;
+    print_array(out, "hemisphere", 1, Values::Int32(hemispheres), config.binary)?;
+    print_array(out, "time", 1, Values::Float32(vec![time as f32; points]), config.binary)?;
+    writeln!(out, "      </PointData>")?;
+    writeln!(out, "    </Piece>")?;
+    writeln!(out, "  </UnstructuredGrid>")?;
+    writeln!(out, "</VTKFile>")?;
+    return out.flush();
+}
+
+/// Sweeps `frames` samples of global time [0, 1], writing each as `evert_NNNN.vtu` into `dir`,
+/// and the ParaView `.pvd` collection tying them together to `sink`.
+///
+/// The collection names the files relative to `dir`, so it belongs there too, e.g.
+/// `evert --format pvd --outdir run > run/evert.pvd`.
+pub fn print_pvd(eversion: &Eversion, frames: usize, parts: Vec<char>, dir: &std::path::Path, sink: &mut dyn Sink) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let mut files: Vec<(f64, String)> = Vec::new();
+    for (i, (time, grid)) in sweep(eversion, frames)?.into_iter().enumerate() {
+        let name: String = format!("evert_{i:04}.vtu");
+        let file: std::io::BufWriter<std::fs::File> = std::io::BufWriter::new(std::fs::File::create(dir.join(&name))?);
+        print_vtu(&grid, time, parts.clone(), &eversion.config, &mut WriterSink::new(file, std::io::sink()))?;
+        writeln!(sink.diagnostics(), "wrote {}", dir.join(&name).display())?;
+        files.push((time, name));
+    };
+    let out: &mut dyn Write = sink.geometry();
+    writeln!(out, r#"<?xml version="1.0"?>"#)?;
+    writeln!(out, r#"<VTKFile type="Collection" version="0.1" byte_order="LittleEndian">"#)?;
+    writeln!(out, "  <Collection>")?;
+    for (time, name) in files {
+        writeln!(out, r#"    <DataSet timestep="{time}" group="" part="0" file="{name}"/>"#)?;
+    };
+    writeln!(out, "  </Collection>")?;
+    writeln!(out, "</VTKFile>")?;
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sink::Sink;
+
+    /// The value of `attribute` on every `<element ` tag, in order.
+    fn attributes<'a>(xml: &'a str, element: &str, attribute: &str) -> Vec<&'a str> {
+        return xml.split(&format!("<{element} ")).skip(1)
+            .map(|tag: &str| {
+                let start: usize = tag.find(&format!("{attribute}=\"")).unwrap() + attribute.len() + 2;
+                &tag[start..start + tag[start..].find('"').unwrap()]
+            })
+            .collect();
+    }
+    #[test]
+    fn pvd_series() {
+        let dir: std::path::PathBuf = std::env::temp_dir().join(format!("evert_pvd_{}", std::process::id()));
+        let pvd: String = crate::export::tests::text(|sink: &mut dyn Sink| super::print_pvd(&crate::Eversion::default(), 3, vec!['+', '0', '-', '1'], &dir, sink));
+        assert_eq!(attributes(&pvd, "DataSet", "timestep"), vec!["0", "0.5", "1"], "{pvd}");
+        let files: Vec<&str> = attributes(&pvd, "DataSet", "file");
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().all(|file: &&str| dir.join(file).is_file()), "{files:?}");
+        let vtu: String = std::fs::read_to_string(dir.join(files[2])).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        // two 13 x 13 pieces of 12 x 12 quads
+        assert_eq!(attributes(&vtu, "Piece", "NumberOfPoints"), vec!["338"]);
+        assert_eq!(attributes(&vtu, "Piece", "NumberOfCells"), vec!["288"]);
+        let names: Vec<&str> = attributes(&vtu, "DataArray", "Name");
+        assert!(names.contains(&"strip") && names.contains(&"TimeValue"), "{names:?}");
+    }
+    #[test]
+    fn vtu_counts() {
+        let grid: crate::Grid = crate::Eversion::default().grid(crate::Sto::Twist, 0.5);
+        let vtu = |binary: bool| -> String {
+            let config: crate::nstrip::EversionConfig = crate::nstrip::EversionConfig { binary, ..Default::default() };
+            return crate::export::tests::text(|sink: &mut dyn Sink| super::print_vtu(&grid, 0.25, vec!['+', '0', '-', '1'], &config, sink));
+        };
+        let (points, quads): (usize, usize) = (2 * (grid.jmax + 1) * (grid.kmax + 1), 2 * grid.jmax * grid.kmax);
+        for binary in [false, true] {
+            let vtu: String = vtu(binary);
+            assert_eq!(attributes(&vtu, "Piece", "NumberOfPoints"), vec![points.to_string()]);
+            assert_eq!(attributes(&vtu, "Piece", "NumberOfCells"), vec![quads.to_string()]);
+            assert_eq!(attributes(&vtu, "DataArray", "Name").len(), 12);
+        };
+
+        let ascii: String = vtu(false);
+        let values = |name: &str| -> Vec<f64> {
+            let at: usize = ascii.find(&format!(r#"Name="{name}""#)).unwrap();
+            let line: &str = ascii[at..].lines().nth(1).unwrap();
+            return line.split_whitespace().map(|x: &str| x.parse::<f64>().unwrap()).collect();
+        };
+        assert_eq!(values("Points").len(), 3 * points);
+        assert_eq!(values("connectivity").len(), 4 * quads);
+        assert!(values("connectivity").iter().all(|i: &f64| (*i as usize) < points));
+        assert_eq!(values("types").len(), quads);
+        let ids: Vec<(f64, f64)> = values("strip").into_iter().zip(values("hemisphere")).collect();
+        assert_eq!(ids.len(), points);
+        assert_eq!(ids.iter().filter(|id: &&(f64, f64)| **id == (0.0, 1.0)).count(), points / 2);
+        assert_eq!(ids.iter().filter(|id: &&(f64, f64)| **id == (1.0, -1.0)).count(), points / 2);
+        let normals: Vec<f64> = values("Normals");
+        assert_eq!(normals.len(), 3 * points);
+        // the poles borrow their normal along u rather than carrying a zero one
+        for n in normals.chunks_exact(3) {
+            assert!((n[0] * n[0] + n[1] * n[1] + n[2] * n[2] - 1.0).abs() < 1e-5, "normal {n:?}");
+        };
+    }
+}
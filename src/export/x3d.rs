@@ -0,0 +1,128 @@
+use std::io::Write;
+
+use crate::{
+    eversion::{Eversion, Grid},
+    export::{pieces, sweep, Piece, Vertex},
+    sink::Sink,
+};
+
+fn values<const N: usize>(values: impl IntoIterator<Item = [f64; N]>, separator: &str) -> String {
+    return values.into_iter()
+        .map(|value: [f64; N]| value.map(|x: f64| format!("{x:.6}")).join(" "))
+        .collect::<Vec<String>>()
+        .join(separator);
+}
+
+/// Writes the whole eversion as a self-animating X3D scene, or a VRML97 world with `vrml`.
+///
+/// One `IndexedFaceSet` of quads holds every selected part at the first frame; a `TimeSensor`
+/// looping over `duration` seconds drives a `CoordinateInterpolator` and a `NormalInterpolator`
+/// keyed at the `frames` times T of `sweep`. Texture coordinates are (u, v), and the scene is
+/// turned Y-up as both formats expect.
+pub fn print_x3d(eversion: &Eversion, frames: usize, duration: f64, parts: Vec<char>, vrml: bool, sink: &mut dyn Sink) -> std::io::Result<()> {
+    let samples: Vec<(f64, Grid)> = sweep(eversion, frames)?;
+    let sampled: Vec<Vec<Piece>> = samples.iter()
+        .map(|(_, grid): &(f64, Grid)| pieces(grid, parts.clone(), &eversion.config))
+        .collect::<std::io::Result<_>>()?;
+    let layout: &Vec<Piece> = &sampled[0];
+    let mut faces: Vec<String> = Vec::new();
+    let mut base: usize = 0;
+    for piece in layout.iter() {
+        faces.extend(piece.quads().into_iter().map(|q: [usize; 4]| format!("{} {} {} {} -1", q[0] + base, q[1] + base, q[2] + base, q[3] + base)));
+        base += piece.vertices.len();
+    };
+    let points = |pieces: &Vec<Piece>| -> Vec<[f64; 3]> { pieces.iter().flat_map(|piece: &Piece| piece.vertices.iter().map(|v: &Vertex| v.position)).collect() };
+    let normals = |pieces: &Vec<Piece>| -> Vec<[f64; 3]> { pieces.iter().flat_map(|piece: &Piece| piece.shading_normals()).collect() };
+    let separator: &str = if vrml { ",\n" } else { ", " };
+    let (point, vector): (String, String) = (values(points(layout), separator), values(normals(layout), separator));
+    let uv: String = values(layout.iter().flat_map(|piece: &Piece| piece.vertices.iter().map(|v: &Vertex| v.uv)), separator);
+    let key: String = samples.iter().map(|(time, _): &(f64, Grid)| format!("{time:.6}")).collect::<Vec<String>>().join(" ");
+    let point_values: String = values(sampled.iter().flat_map(points), separator);
+    let vector_values: String = values(sampled.iter().flat_map(normals), separator);
+    let out: &mut dyn Write = sink.geometry();
+
+    if vrml {
+        writeln!(out, "#VRML V2.0 utf8")?;
+        writeln!(out, "# everting sphere, {} frames of T = 0..1", samples.len())?;
+        writeln!(out, "Transform {{ rotation 1 0 0 -1.570796 children [")?;
+        writeln!(out, "  Shape {{")?;
+        writeln!(out, "    appearance Appearance {{ material Material {{ diffuseColor 0.85 0.62 0.18 }} }}")?;
+        writeln!(out, "    geometry IndexedFaceSet {{")?;
+        writeln!(out, "      solid FALSE ccw TRUE normalPerVertex TRUE")?;
+        writeln!(out, "      coord DEF Points Coordinate {{ point [\n{point}\n      ] }}")?;
+        writeln!(out, "      normal DEF Normals Normal {{ vector [\n{vector}\n      ] }}")?;
+        writeln!(out, "      texCoord TextureCoordinate {{ point [\n{uv}\n      ] }}")?;
+        writeln!(out, "      coordIndex [\n{}\n      ]", faces.join(",\n"))?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "  }}")?;
+        writeln!(out, "] }}")?;
+        writeln!(out, "DEF Clock TimeSensor {{ cycleInterval {duration} loop TRUE }}")?;
+        writeln!(out, "DEF Move CoordinateInterpolator {{ key [ {key} ] keyValue [\n{point_values}\n] }}")?;
+        writeln!(out, "DEF Turn NormalInterpolator {{ key [ {key} ] keyValue [\n{vector_values}\n] }}")?;
+        writeln!(out, "ROUTE Clock.fraction_changed TO Move.set_fraction")?;
+        writeln!(out, "ROUTE Move.value_changed TO Points.set_point")?;
+        writeln!(out, "ROUTE Clock.fraction_changed TO Turn.set_fraction")?;
+        writeln!(out, "ROUTE Turn.value_changed TO Normals.set_vector")?;
+    } else {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(out, "<!DOCTYPE X3D PUBLIC \"ISO//Web3D//DTD X3D 3.3//EN\" \"http://www.web3d.org/specifications/x3d-3.3.dtd\">")?;
+        writeln!(out, "<X3D profile=\"Interchange\" version=\"3.3\">")?;
+        writeln!(out, "  <head><meta name=\"description\" content=\"everting sphere, {} frames of T = 0..1\"/></head>", samples.len())?;
+        writeln!(out, "  <Scene>")?;
+        writeln!(out, "    <Transform rotation=\"1 0 0 -1.570796\">")?;
+        writeln!(out, "      <Shape>")?;
+        writeln!(out, "        <Appearance><Material diffuseColor=\"0.85 0.62 0.18\"/></Appearance>")?;
+        writeln!(out, "        <IndexedFaceSet solid=\"false\" ccw=\"true\" normalPerVertex=\"true\" coordIndex=\"{}\">", faces.join(" "))?;
+        writeln!(out, "          <Coordinate DEF=\"Points\" point=\"{point}\"/>")?;
+        writeln!(out, "          <Normal DEF=\"Normals\" vector=\"{vector}\"/>")?;
+        writeln!(out, "          <TextureCoordinate point=\"{uv}\"/>")?;
+        writeln!(out, "        </IndexedFaceSet>")?;
+        writeln!(out, "      </Shape>")?;
+        writeln!(out, "    </Transform>")?;
+        writeln!(out, "    <TimeSensor DEF=\"Clock\" cycleInterval=\"{duration}\" loop=\"true\"/>")?;
+        writeln!(out, "    <CoordinateInterpolator DEF=\"Move\" key=\"{key}\" keyValue=\"{point_values}\"/>")?;
+        writeln!(out, "    <NormalInterpolator DEF=\"Turn\" key=\"{key}\" keyValue=\"{vector_values}\"/>")?;
+        writeln!(out, "    <ROUTE fromNode=\"Clock\" fromField=\"fraction_changed\" toNode=\"Move\" toField=\"set_fraction\"/>")?;
+        writeln!(out, "    <ROUTE fromNode=\"Move\" fromField=\"value_changed\" toNode=\"Points\" toField=\"set_point\"/>")?;
+        writeln!(out, "    <ROUTE fromNode=\"Clock\" fromField=\"fraction_changed\" toNode=\"Turn\" toField=\"set_fraction\"/>")?;
+        writeln!(out, "    <ROUTE fromNode=\"Turn\" fromField=\"value_changed\" toNode=\"Normals\" toField=\"set_vector\"/>")?;
+        writeln!(out, "  </Scene>")?;
+        writeln!(out, "</X3D>")?;
+    };
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn interpolators_match_the_mesh() {
+        let eversion: crate::Eversion = crate::Eversion::default();
+        let world = |vrml: bool| -> String {
+            return crate::export::tests::text(|sink: &mut dyn crate::sink::Sink| super::print_x3d(&eversion, 4, 6.0, vec!['+', '0', '-', '0'], vrml, sink));
+        };
+        let x3d: String = world(false);
+        let attribute = |name: &str| -> Vec<f64> {
+            let start: usize = x3d.find(&format!(" {name}=\"")).unwrap() + name.len() + 3;
+            let end: usize = start + x3d[start..].find('"').unwrap();
+            x3d[start..end].split([' ', ',']).filter(|x: &&str| !x.is_empty()).map(|x: &str| x.parse::<f64>().unwrap()).collect()
+        };
+        let (point, key, key_value): (Vec<f64>, Vec<f64>, Vec<f64>) = (attribute("point"), attribute("key"), attribute("keyValue"));
+        assert_eq!(point.len(), 3 * 2 * 13 * 13);
+        assert_eq!(key, vec![0.0, 0.333333, 0.666667, 1.0]);
+        // one full set of points per key, the first where the mesh is
+        assert_eq!(key_value.len(), key.len() * point.len());
+        assert_eq!(&key_value[..point.len()], &point[..]);
+        assert_eq!(attribute("coordIndex").iter().filter(|i: &&f64| **i == -1.0).count(), 2 * 12 * 12);
+        assert_eq!(x3d.matches("<ROUTE ").count(), 4);
+        assert_eq!(attribute("cycleInterval"), vec![6.0]);
+
+        let vrml: String = world(true);
+        assert!(vrml.starts_with("#VRML V2.0 utf8\n"));
+        let clock: Vec<&str> = vrml.lines().find(|line: &&str| line.starts_with("DEF Clock TimeSensor")).unwrap().split_whitespace().collect();
+        assert_eq!(clock[clock.iter().position(|token: &&str| *token == "cycleInterval").unwrap() + 1], "6");
+        // the same four routes as in X3D
+        let routes: Vec<&str> = vrml.lines().filter(|line: &&str| line.starts_with("ROUTE ")).collect();
+        assert_eq!(routes.len(), 4);
+        assert_eq!(vrml.matches('[').count(), vrml.matches(']').count());
+    }
+}
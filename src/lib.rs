@@ -0,0 +1,32 @@
+#![allow(clippy::needless_return)] // NO
+
+//! Everting sphere, after "Outside In" by The Geometry Center.
+//!
+//! The [`Eversion`] type samples the surface of the sphere for a given
+//! [`Sto`] stage (or a global time) and returns the geometry in memory;
+//! [`spline::print_scene`] turns that geometry into Geomview/OOGL.
+
+pub mod twojet;
+pub mod twojetvec;
+
+pub mod threejet;
+pub mod threejetvec;
+
+pub mod nstrip;
+mod figureeight;
+
+mod c_gformat;
+pub mod points;
+
+pub mod sphere;
+pub mod spline;
+pub mod sink;
+pub mod transform;
+
+pub mod eversion;
+pub mod export;
+
+pub use eversion::{Eversion, Grid, Sampling, SurfacePoint, Timeline};
+pub use sink::{Sink, StdSink, WriterSink};
+pub use transform::Transform;
+pub use sphere::{Eversible, Sto};
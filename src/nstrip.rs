@@ -1,25 +1,26 @@
-use std::sync::atomic::{AtomicI32, AtomicBool, Ordering};
+use crate::transform::Transform;
 
 pub static ALLPARTS: bool = cfg!(feature="allparts");
 
-pub static N_STRIPS: AtomicI32  = AtomicI32::new(8);
-pub static BREZIER:  AtomicBool = AtomicBool::new(false);
-pub static BINARY:   AtomicBool = AtomicBool::new(false);
-
-pub trait EasyAtomic<T> {
-    type T;
-    fn get(&self) -> T;
-    fn set(&self, value: T);
+/// Settings of one eversion, shared by the surface math and the emitters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EversionConfig {
+    /// Number of strips the sphere is cut into; v = 1 at longitude 2pi/n_strips.
+    pub n_strips: i32,
+    /// Emit OOGL binary instead of human readable text.
+    pub binary:   bool,
+    /// Emit Bezier patches instead of meshes.
+    pub bezier:   bool,
+    /// Apply the strip replication matrices to the vertices instead of emitting them as INST/TLIST.
+    pub baked:    bool,
+    /// Placement of the emitted sphere; the unit sphere is emitted as is by default.
+    pub transform: Transform,
 }
 
-impl EasyAtomic<i32> for AtomicI32 {
-    type T = i32;
-    fn get(&self) -> i32 { self.load(Ordering::Relaxed) }
-    fn set(&self, value: i32) { self.store(value, Ordering::Relaxed) }
+impl Default for EversionConfig {
+    fn default() -> Self { Self { n_strips: 8, binary: false, bezier: false, baked: false, transform: Transform::identity() } }
 }
 
-impl EasyAtomic<bool> for AtomicBool {
-    type T = bool;
-    fn get(&self) -> bool { self.load(Ordering::Relaxed) }
-    fn set(&self, value: bool) { self.store(value, Ordering::Relaxed) }
-}
\ No newline at end of file
+impl EversionConfig {
+    pub fn new(n_strips: i32, binary: bool, bezier: bool) -> Self { Self { n_strips, binary, bezier, baked: false, transform: Transform::identity() } }
+}
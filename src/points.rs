@@ -19,7 +19,9 @@ impl BrezierPoint {
     pub fn g(mut self) -> Self { self.ftm = FormatType::CG; return self; }
     #[allow(unused)]    
     pub fn f(mut self) -> Self { self.ftm = FormatType::CF; return self; }
-    pub fn as_bytes(&self) -> [u8; 24] { [self.x.to_be_bytes(), self.y.to_be_bytes(), self.z.to_be_bytes()].concat().try_into().unwrap() }
+    pub fn xyz(&self) -> [f64; 3] { [self.x, self.y, self.z] }
+    /// OOGL binary form: big-endian 32-bit floats.
+    pub fn as_bytes(&self) -> [u8; 12] { [(self.x as f32).to_be_bytes(), (self.y as f32).to_be_bytes(), (self.z as f32).to_be_bytes()].concat().try_into().unwrap() }
 }
 
 pub struct SplinePoint { x: f64, y: f64, z: f64, nx: f64, ny: f64, nz: f64, s: f64/*, g: FormatType */ }
@@ -43,6 +45,11 @@ impl std::fmt::Display for SplinePoint {
 
 impl SplinePoint {
     pub fn new(x: f64, y: f64, z: f64, nx: f64, ny: f64, nz: f64, s: f64) -> Self { Self { x, y, z, nx, ny, nz, s } }
+    /// OOGL binary form, position then unit normal: big-endian 32-bit floats.
+    pub fn as_bytes(&self) -> [u8; 24] {
+        [self.x, self.y, self.z, self.nx * self.s, self.ny * self.s, self.nz * self.s]
+            .map(|c: f64| (c as f32).to_be_bytes()).concat().try_into().unwrap()
+    }
     #[allow(unused)]
     pub fn zero() -> Self { Self { x:  0.0, y:  0.0, z:  0.0, nx: 0.0, ny: 0.0, nz: 0.0, s: 0.0 } }
     // pub fn g(self) -> Self { Self { x: self.x, y: self.y, z: self.z, nx: self.nx, ny: self.ny, nz: self.nz, s: self.s, g: true } }
@@ -0,0 +1,47 @@
+use std::io::Write;
+
+/// Destination of everything the emitters write.
+///
+/// Geometry is the OOGL stream itself; diagnostics are side notes, such as the RenderMan
+/// `Declare` lines, which must never end up interleaved with the geometry.
+pub trait Sink {
+    fn geometry(&mut self) -> &mut dyn Write;
+    fn diagnostics(&mut self) -> &mut dyn Write;
+}
+
+/// Geometry to stdout, diagnostics to stderr; what the `evert` binary uses.
+pub struct StdSink {
+    out: std::io::StdoutLock<'static>,
+    err: std::io::Stderr,
+}
+
+impl Default for StdSink {
+    fn default() -> Self { Self::new() }
+}
+
+impl StdSink {
+    pub fn new() -> Self { Self { out: std::io::stdout().lock(), err: std::io::stderr() } }
+}
+
+impl Sink for StdSink {
+    fn geometry(&mut self) -> &mut dyn Write { &mut self.out }
+    fn diagnostics(&mut self) -> &mut dyn Write { &mut self.err }
+}
+
+/// Any pair of writers: files, buffers, sockets...
+///
+/// Use [`std::io::sink()`] as `diagnostics` to drop them.
+pub struct WriterSink<G: Write, D: Write> {
+    pub geometry:    G,
+    pub diagnostics: D,
+}
+
+impl<G: Write, D: Write> WriterSink<G, D> {
+    pub fn new(geometry: G, diagnostics: D) -> Self { Self { geometry, diagnostics } }
+    pub fn into_inner(self) -> (G, D) { (self.geometry, self.diagnostics) }
+}
+
+impl<G: Write, D: Write> Sink for WriterSink<G, D> {
+    fn geometry(&mut self) -> &mut dyn Write { &mut self.geometry }
+    fn diagnostics(&mut self) -> &mut dyn Write { &mut self.diagnostics }
+}
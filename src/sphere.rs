@@ -1,4 +1,4 @@
-use crate::{threejet::ThreeJet, twojetvec::TwoJetVec, threejetvec::ThreeJetVec, figureeight::add_figure_eight};
+use crate::{threejet::ThreeJet, twojetvec::TwoJetVec, threejetvec::ThreeJetVec, figureeight::add_figure_eight, nstrip::EversionConfig};
 
 /// Surface Time Operations
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -46,12 +46,12 @@ trait Staged {
 }
 
 pub trait Eversible {
-    fn corrugate(&self, v: Self, t: f64) -> TwoJetVec;
-    fn push_through(&self, v: Self, t: f64) -> TwoJetVec;
-    fn twist(&self, v: Self, t: f64) -> TwoJetVec;
-    fn unpush(&self, v: Self, t: f64) -> TwoJetVec;
-    fn uncorrugate(&self, v: Self, t: f64) -> TwoJetVec;
-    fn bend_in(&self, v: Self, t: f64) -> TwoJetVec;
+    fn corrugate(&self, v: Self, t: f64, config: &EversionConfig) -> TwoJetVec;
+    fn push_through(&self, v: Self, t: f64, config: &EversionConfig) -> TwoJetVec;
+    fn twist(&self, v: Self, t: f64, config: &EversionConfig) -> TwoJetVec;
+    fn unpush(&self, v: Self, t: f64, config: &EversionConfig) -> TwoJetVec;
+    fn uncorrugate(&self, v: Self, t: f64, config: &EversionConfig) -> TwoJetVec;
+    fn bend_in(&self, v: Self, t: f64, config: &EversionConfig) -> TwoJetVec;
 }
 
 impl Bendable for ThreeJet {
@@ -164,14 +164,15 @@ impl Staged for ThreeJet {
 
 
         let p1:     Self = self.param_1();
+        let p2:     Self = self.param_2();
         let interp: Self = self.u_interp();
 
         let tj:     Self = Self::new_simple(tt, 0.0, 0.0);
         
         let p1a1:   ThreeJetVec = p1.arc(rhs, 0.9, 0.9, -1.0);
-        let p1a2:   ThreeJetVec = p1.arc(rhs, 1.0, 1.0, 0.5);
+        let p2a2:   ThreeJetVec = p2.arc(rhs, 1.0, 1.0, 0.5);
 
-        return p1a1.rotated_z(tj).interpolated(p1a2, interp);
+        return p1a1.rotated_z(tj).interpolated(p2a2.rotated_y(t), interp);
     }
     fn scene_34(&self, rhs: Self, t: f64) -> ThreeJetVec {
         self.stage_3(rhs).interpolated(self.stage_4(rhs), Self::t_interp(t))
@@ -179,42 +180,42 @@ impl Staged for ThreeJet {
 }
 
 impl Eversible for ThreeJet {
-    fn bend_in(&self, rhs: Self, t: f64) -> TwoJetVec {
+    fn bend_in(&self, rhs: Self, t: f64, config: &EversionConfig) -> TwoJetVec {
         let ti:   Self = Self::t_interp(t);
         let fv1:  Self = Self::new_simple(0.0, 0.0, 1.0);
         let zero: Self = Self::zero();
-        return add_figure_eight(self.scene_01(fv1, ti.into()), *self, rhs.into(), zero, self.fs_interp());
+        return add_figure_eight(self.scene_01(fv1, ti.into()), *self, rhs.into(), zero, self.fs_interp(), config);
     }
-    fn corrugate(&self, rhs: Self, t: f64) -> TwoJetVec {
+    fn corrugate(&self, rhs: Self, t: f64, config: &EversionConfig) -> TwoJetVec {
         let t: ThreeJet = Self::t_interp(t);
         return add_figure_eight(
             self.stage_1(Self::new_simple(0.0, 0.0, 1.0)),
-            *self, rhs.into(), self.ff_interp() * t, self.fs_interp()
+            *self, rhs.into(), self.ff_interp() * t, self.fs_interp(), config
         );
     }
-    fn push_through(&self, rhs: Self, t: f64) -> TwoJetVec {
+    fn push_through(&self, rhs: Self, t: f64, config: &EversionConfig) -> TwoJetVec {
         return add_figure_eight(
             self.scene_12(Self::new_simple(0.0, 0.0, 1.0), t),
-            *self, rhs.into(), self.ff_interp(), self.fs_interp()
+            *self, rhs.into(), self.ff_interp(), self.fs_interp(), config
         );
     }
-    fn twist(&self, rhs: Self, t: f64) -> TwoJetVec {
+    fn twist(&self, rhs: Self, t: f64, config: &EversionConfig) -> TwoJetVec {
         return add_figure_eight(
             self.scene_23(Self::new_simple(0.0, 0.0, 1.0), t),
-            *self, rhs.into(), self.ff_interp(), self.fs_interp()
+            *self, rhs.into(), self.ff_interp(), self.fs_interp(), config
         );
     }
-    fn unpush(&self, rhs: Self, t: f64) -> TwoJetVec {
+    fn unpush(&self, rhs: Self, t: f64, config: &EversionConfig) -> TwoJetVec {
         return add_figure_eight(
             self.scene_34(Self::new_simple(0.0, 0.0, 1.0), t),
-            *self, rhs.into(), self.ff_interp(), self.fs_interp()
+            *self, rhs.into(), self.ff_interp(), self.fs_interp(), config
         );
     }
-    fn uncorrugate(&self, rhs: Self, t: f64) -> TwoJetVec {
-        let t: ThreeJet = Self::t_interp(t * -1.0 + 1.0);
+    fn uncorrugate(&self, rhs: Self, t: f64, config: &EversionConfig) -> TwoJetVec {
+        let t: ThreeJet = Self::t_interp(1.0 - t);
         return add_figure_eight(
             self.stage_4(Self::new_simple(0.0, 0.0, 1.0)),
-            *self, rhs.into(), self.ff_interp() * t, self.fs_interp()
+            *self, rhs.into(), self.ff_interp() * t, self.fs_interp(), config
         );
     }
 }
\ No newline at end of file
@@ -1,8 +1,13 @@
+use std::io::Write;
+
 use crate::{
-	nstrip::{ N_STRIPS, BREZIER, BINARY, EasyAtomic },
+	nstrip::EversionConfig,
 	twojetvec::TwoJetVec,
-	threejet::ThreeJet,
-	sphere::{Eversible, Sto}, c_gformat::{str_to_i64, signof},
+	eversion::Grid,
+	sink::Sink,
+	transform::Transform,
+	c_gformat::{str_to_i64, signof},
+	export::selected_parts,
 };
 
 static PART_POS: u8 = 0x1;
@@ -10,28 +15,35 @@ static PART_NEG: u8 = 0x2;
 
 pub trait PrintableSpline {
 	#[allow(clippy::too_many_arguments)] // aguantese como hombre
-	fn print_spline(&self, v01: TwoJetVec,  v10: TwoJetVec, v11: TwoJetVec, us: f64, vs: f64, s0: f64, s1: f64, t0: f64, t1: f64);
+	fn print_spline(&self, v01: TwoJetVec,  v10: TwoJetVec, v11: TwoJetVec, us: f64, vs: f64, s0: f64, s1: f64, t0: f64, t1: f64, config: &EversionConfig, out: &mut dyn Write) -> std::io::Result<()>;
 }
 
 type TwoJetVVV = Vec<Vec<TwoJetVec>>;
-type SpeedVec  = Vec<f64>;
-type AccelVec  = Vec<SpeedVec>;
 
-fn print_transforms(x: f64, y: f64, z: f64, w: f64) {
+fn print_transforms(out: &mut dyn Write, x: f64, y: f64, z: f64, w: f64) -> std::io::Result<()> {
 	let (xs, ys, zs, ws): (char, char, char, char) = (signof(x.signum()), signof(y.signum()), signof(z.signum()), signof(w.signum()));
 	let (x, y, z, w): (f64, f64, f64, f64) = (x.abs(), y.abs(), z.abs(), w.abs());
-	println!("\t{xs}{x:1.6} {ys}{y:1.6} {zs}{z:1.6} {ws}{w:1.6}");
+	writeln!(out, "\t{xs}{x:1.6} {ys}{y:1.6} {zs}{z:1.6} {ws}{w:1.6}")
+}
+
+/// One replica of the standard unit (u=0..1, v=0..1): strip `strip` of the `sign` ('+' or '-') hemisphere.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Part {
+	pub sign:      char,
+	pub strip:     usize,
+	pub transform: Transform,
 }
 
-fn print_part_side(partlist: &[u8], idx: bool) {
-	let j: f64 = idx as i32 as f64;
+fn part_side(partlist: &[u8], idx: bool, n_strips: i32) -> Vec<Part> {
+	let j: f64 = if idx { -1.0 } else { 1.0 };
 	let mut csign: char;
 	let mut psign: u8;
 	let mut jk: f64;
+	let mut side: Vec<Part> = Vec::new();
 	
-	for (k, part) in partlist.iter().enumerate().take(N_STRIPS.get() as usize) {
+	for (k, part) in partlist.iter().enumerate().take(n_strips as usize) {
 		if idx {
-			jk =  N_STRIPS.get() as f64 - 1.0 - k as f64;
+			jk =  n_strips as f64 - 1.0 - k as f64;
 			psign = PART_NEG;
 			csign = '-';
 		} else {
@@ -41,186 +53,288 @@ fn print_part_side(partlist: &[u8], idx: bool) {
 		};
 
 		if (*part & psign) > 0 {
-			let t: f64 = 2.0 * std::f64::consts::PI * jk / N_STRIPS.get() as f64;
+			let t: f64 = 2.0 * std::f64::consts::PI * jk / n_strips as f64;
 			let s: f64 = t.sin();
 			let c: f64 = t.cos();
-			// fprintf(fp, "# %c%d of %d\n", j < 0 ? '-' : '+', k, N_STRIPS.get());
-			println!("# {sign}{k} of {ns}", sign=csign, k=k, ns=N_STRIPS.get());
-			// fprintf(fp, "\t%10f %10f %10f %10f\n", j * c, -s, 0., 0.);
-			print_transforms(j*c, -s,  0.0, 0.0);
-			// fprintf(fp, "\t%10f %10f %10f %10f\n", j * s, c, 0., 0.);
-			print_transforms(j*s, c,   0.0, 0.0);
-			// fprintf(fp, "\t%10f %10f %10f %10f\n", 0., 0., (double)j, 0.);
-			print_transforms(0.0, 0.0, j,   0.0);
-			// fprintf(fp, "\t%10f %10f %10f %10f\n", 0., 0., 0., 1.);
-			print_transforms(0.0, 0.0, 0.0, 1.0);
+			side.push(Part { sign: csign, strip: k, transform: Transform::new([
+				[j*c, -s,  0.0, 0.0],
+				[j*s, c,   0.0, 0.0],
+				[0.0, 0.0, j,   0.0],
+				[0.0, 0.0, 0.0, 1.0],
+			])});
 		};
 	};
+	return side;
 }
 
-fn calc_speed_v(oper: Sto, u: f64, t: f64) -> f64 {
-	let nu: ThreeJet = ThreeJet::new_simple(u, 1.0, 0.0);
-	let vu: ThreeJet = ThreeJet::new_simple(0.0, 0.0, 0.1);
-
-	let o: f64 = match oper {
-    	Sto::Corrugate => { nu.corrugate(vu, t) },
-    	Sto::PushThrough => { nu.push_through(vu, t) },
-    	Sto::Twist => { nu.twist(vu, t) },
-    	Sto::UnPush => { nu.unpush(vu, t) },
-    	Sto::UnCorrugate => { nu.uncorrugate(vu, t) },
-    	Sto::BendIn => { nu.bend_in(vu, t) },
-	}.calc_speed_v();
-
-	if o != 0.0 {
-		return o;
-	} else {
-		return calc_speed_v(oper, u + if u < 1.0 { 1e-9 } else { -1e-9 }, t);
-	};
+/// Matrices replicating the standard unit onto every strip selected in `partlist`, -Z hemisphere first.
+pub fn part_transforms(partlist: &[u8], n_strips: i32) -> Vec<Part> {
+	let mut parts: Vec<Part> = part_side(partlist, true, n_strips);
+	parts.append(&mut part_side(partlist, false, n_strips));
+	return parts;
 }
 
-fn calc_speed_u(oper: Sto, u: f64, v: f64, t: f64) -> TwoJetVec {
-	let nu: ThreeJet = ThreeJet::new_simple(u, 1.0, 0.0);
-	let vu: ThreeJet = ThreeJet::new_simple(v, 0.0, 1.0);
-
-	return match oper {
-    	Sto::Corrugate => { nu.corrugate(vu, t) },
-    	Sto::PushThrough => { nu.push_through(vu, t) },
-    	Sto::Twist => { nu.twist(vu, t) },
-    	Sto::UnPush => { nu.unpush(vu, t) },
-    	Sto::UnCorrugate => { nu.uncorrugate(vu, t) },
-    	Sto::BendIn => { nu.bend_in(vu, t) },
+pub(crate) fn print_part_side(out: &mut dyn Write, parts: &[Part], n_strips: i32, transform: &Transform) -> std::io::Result<()> {
+	for part in parts {
+		// fprintf(fp, "# %c%d of %d\n", j < 0 ? '-' : '+', k, n_strips);
+		writeln!(out, "# {sign}{k} of {ns}", sign=part.sign, k=part.strip, ns=n_strips)?;
+		for [x, y, z, w] in part.transform.then(transform).m {
+			print_transforms(out, x, y, z, w)?;
+		};
 	};
+	return Ok(());
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn print_scene(oper: Sto, umin: f64, umax: f64, adu: f64, vmin: f64, vmax: f64, adv: f64, t: f64, parts: Vec<char>) { 
-	let (mut u, mut v): (f64, f64);
-	let (mut ju, mut ku): (usize, usize);
-	
-	let jmax: i32 = (((umax - umin).abs() / adu + 0.5) as i32).max(1);
-	let kmax: i32 = (((vmax - vmin).abs() / adv + 0.5) as i32).max(1);
-
-	let du: f64 = (umax - umin) / jmax as f64;
-	let dv: f64 = (vmax - vmin) / kmax as f64;
+fn print_geometry(out: &mut dyn Write, grid: &Grid, config: &EversionConfig) -> std::io::Result<()> {
+	let (jmax, kmax): (usize, usize) = (grid.jmax, grid.kmax);
+	let values: &TwoJetVVV = &grid.values;
 
-	let mut values: TwoJetVVV = Vec::with_capacity((jmax + 1) as usize);
-	let mut speedu: AccelVec  = Vec::with_capacity((jmax + 1) as usize);
-	let mut speedv: SpeedVec  = Vec::with_capacity((jmax + 1) as usize);
+	if config.bezier {
+		writeln!(out, "{{ STBBP{}", if config.binary { " BINARY" } else { "" })?;
+		for j in 0..jmax {
+			for k in 0..kmax {
+				values[j][k].print_spline(
+					values[j][k + 1], values[j + 1][k], values[j + 1][k + 1],
+					grid.du, grid.dv,
+					grid.u(j), grid.u(j + 1),
+					grid.v(k), grid.v(k + 1),
+					config, out
+				)?;
+			};
+		};
+	} else {
+		let nu: i32 = kmax as i32 + 1;
+		let nv: i32 = jmax as i32 + 1;
+		writeln!(out, "{{ NMESH{}", if config.binary { " BINARY" } else { "" })?;
 
-	for j in 0..jmax {
-		ju = j as usize;
-		u = umin + du * (j as f64);
+		if config.binary {
+			out.write_all(&nu.to_be_bytes())?;
+			out.write_all(&nv.to_be_bytes())?;
+		} else {
+			writeln!(out, "{} {}", nu, nv)?;
+		}
+		for valuej in values.iter() {
+			for valuejk in valuej.iter() {
+				if config.binary {
+					out.write_all(&valuejk.point(None).as_bytes())?;
+				} else {
+					writeln!(out, "{}", valuejk.point(None))?;
+				};
+			};
+			if !config.binary { writeln!(out)? };
+		}
+	}
+	return writeln!(out, "}}");
+}
 
-		values.push(vec![TwoJetVec::zero(); (kmax + 1) as usize]);
-		speedu.push(vec![0.0;               (kmax + 1) as usize]);
-		speedv.push(calc_speed_v(oper, u, t));
+/// Writes `grid` as Geomview/OOGL, replicated over the strips selected by `parts`.
+///
+/// Replicas are emitted as an `INST` over a `TLIST` of matrices, or, with `config.baked`, as a
+/// `LIST` of copies with the matrices already applied to their vertices.
+pub fn print_scene(grid: &Grid, parts: Vec<char>, config: &EversionConfig, sink: &mut dyn Sink) -> std::io::Result<()> {
+	let hp: bool = !parts.is_empty();
 
-		for k in 0..kmax {
-			v = vmin + dv * k as f64;
-			ku = k as usize;
-			values[ju][ku] = calc_speed_u(oper, u, v, t);
-			speedu[ju][ju] = values[ju][ku].calc_speed_u();
-		};
-	};
+	writeln!(sink.diagnostics(), "Declare \"speeds\" \"varying float\"")?;
+	writeln!(sink.diagnostics(), "Declare \"speedt\" \"varying float\"")?;
 
-	let hp: bool = !parts.is_empty();
-	eprintln!("Declare \"speeds\" \"varying float\"");
-	eprintln!("Declare \"speedt\" \"varying float\"");
+	let out: &mut dyn Write = sink.geometry();
 	if hp {
 		/* Construct matrices to replicate standard unit (u=0..1, v=0..1) into
 		 * complete sphere. */
 
-		let partlist: Vec<u8> = parse_parts(parts);
-		
-		assert!(!partlist.is_empty());
-
-		println!("{{ INST transforms {{ TLIST");
-		
-		print_part_side(&partlist, true);
-		print_part_side(&partlist, false);
+		let replicas: Vec<Part> = selected_parts(parts, config)?;
 
-		print!("}}\ngeom ");
-	}
-	if BREZIER.get() {
-		println!("{{ STBBP{}", if BINARY.get() { " BINARY" } else { "" });
-		for j in 0..jmax as usize {
-			// this was ther, doing nothing...
-			// u = umin + j as f64 * du;
-			for k in 0..kmax as usize {
-				// this was ther, doing nothing...
-				// v = vmin + k as f64 * dv;
-				values[j][k].print_spline(
-					values[j][k + 1], values[j + 1][k], values[j + 1][k + 1],
-					du, dv,
-					umin + j as f64 * du, umin + (j + 1) as f64 * du,
-					vmin + k as f64 * dv, vmin + (k + 1) as f64 * dv
-				);
+		if config.baked {
+			writeln!(out, "{{ LIST")?;
+			for part in replicas.iter() {
+				writeln!(out, "# {sign}{k} of {ns}", sign=part.sign, k=part.strip, ns=config.n_strips)?;
+				print_geometry(out, &grid.transformed(&part.transform.then(&config.transform)), config)?;
 			};
+		} else {
+			writeln!(out, "{{ INST transforms {{ TLIST")?;
+			print_part_side(out, &replicas, config.n_strips, &config.transform)?;
+			write!(out, "}}\ngeom ")?;
+			print_geometry(out, grid, config)?;
 		};
+		writeln!(out, "}}")?;
+	} else if config.transform.is_identity() {
+		print_geometry(out, grid, config)?;
 	} else {
-		let nu: i32 = kmax + 1;
-		let nv: i32 = jmax + 1;
-		println!("{{ NMESH{}", if BINARY.get() { " BINARY" } else { "" });
-
-		if BINARY.get() {
-			std::io::Write::write(&mut std::io::stdout(), &nu.to_be_bytes()).unwrap();
-			std::io::Write::write(&mut std::io::stdout(), &nv.to_be_bytes()).unwrap();
-		} else {
-			println!("{} {}", nu, nv);
-		}
-		for valuej in values.iter().take(jmax as usize) {
-			for valuejk in valuej.iter().take(kmax as usize) {
-				println!("{}",valuejk.point(None)); }
-			if !BINARY.get() { println!() };
-		}
-	}
-	if hp { println!(" }}"); };
-	println!("}}");
+		print_geometry(out, &grid.transformed(&config.transform), config)?;
+	};
+	return out.flush();
 }
 
-fn parse_parts(parts: Vec<char>) -> Vec<u8> {
-	/* Construct matrices to replicate standard unit (u=0..1, v=0..1) into
-	 * complete sphere.  */
-	let mut partlist: Vec<u8> = vec![0; N_STRIPS.get() as usize];
-	let mut sign:     char    = char::default();
-	let mut ncp: 	  usize   = 0;
+/// Parses a part selection such as `+0-0+2+4+6` or `*` into per-strip `PART_POS | PART_NEG` bits.
+///
+/// Each strip number, or `*` for every strip, may be preceded by `+` or `-` to pick one hemisphere; unsigned picks both.
+pub fn parse_parts(parts: Vec<char>, n_strips: i32) -> Result<Vec<u8>, String> {
+	let mut partlist: Vec<u8> = vec![0; n_strips as usize];
+	let mut idx:      usize   = 0;
+	let mut ncp:      usize   = 0;
+	let mut bits:     u8;
 
-	let mut slice: &[char];
-	let mut bits:  u8;
+	while idx < parts.len() {
+		let part: char = parts[idx];
+		if part == ' ' || part == ',' { idx += 1; continue; };
 
-	assert!(parts.contains(&'+') || parts.contains(&'-') || parts.contains(&'*'), "Partlist must contain at least one '+', '-', or '*'");
-
-	for (idx, &part) in parts.iter().enumerate() {
-		if part == ' ' || part == ',' { sign = dbg!(part); continue; };
-		if dbg!(ncp) > 0 { ncp -= 1; continue; };
-		
-		if sign == '+' {
+		if part == '+' {
 			bits = PART_POS;
-		} else if sign == '-' {
+			idx += 1;
+		} else if part == '-' {
 			bits = PART_NEG;
+			idx += 1;
 		} else {
 			bits = PART_POS | PART_NEG;
 		};
-		
-		if part == '*' {
+
+		if parts.get(idx) == Some(&'*') {
 			for char in partlist.iter_mut() {
 				*char |= bits;
 			};
-			continue;
+			idx += 1;
+		} else if parts.get(idx).is_some_and(char::is_ascii_digit) {
+			let j: i64 = str_to_i64(&parts[idx..], &mut ncp, 10).map_err(|e| e.to_string())?;
+			if j < 0 || j >= n_strips.into() {
+				return Err(format!("evert -parts: bad strip number {}; must be in range 0..{}", j, n_strips - 1));
+			};
+			partlist[j as usize] |= bits;
+			idx += ncp + 1;
 		} else {
-			slice = &parts[idx..];
+			return Err(String::from("evert -parts: expected string with alternating signs and strip numbers or a single *"));
+		};
+	};
+	return Ok(partlist);
+}
 
-			let j: i64 = str_to_i64(slice, &mut ncp, 10).unwrap();
-			if part == slice[ncp] {
-				panic!("evert -parts: expected string with alternating signs and strip numbers or a single *");
+#[cfg(test)]
+mod tests {
+	/// Body of a lone `{ KEYWORD BINARY` object, as big-endian 32-bit words.
+	fn read_binary(data: &[u8], keyword: &str) -> Vec<[u8; 4]> {
+		let header: String = format!("{{ {keyword} BINARY\n");
+		assert!(data.starts_with(header.as_bytes()), "missing {header:?}");
+		assert!(data.ends_with(b"}\n"));
+		let body: &[u8] = &data[header.len()..data.len() - 2];
+		assert_eq!(body.len() % 4, 0);
+		return body.chunks_exact(4).map(|w: &[u8]| w.try_into().unwrap()).collect();
+	}
+	fn print_binary(grid: &crate::Grid, config: &crate::nstrip::EversionConfig) -> Vec<u8> {
+		let mut sink = crate::WriterSink::new(Vec::<u8>::new(), std::io::sink());
+		super::print_scene(grid, vec![], config, &mut sink).unwrap();
+		return sink.into_inner().0;
+	}
+	#[test]
+	fn nmesh_binary_round_trip() {
+		let config: crate::nstrip::EversionConfig = crate::nstrip::EversionConfig::new(8, true, false);
+		let grid: crate::Grid = crate::Eversion { config, ..Default::default() }.grid(crate::Sto::PushThrough, 0.4);
+		let words: Vec<[u8; 4]> = read_binary(&print_binary(&grid, &config), "NMESH");
+
+		let (nu, nv): (i32, i32) = (i32::from_be_bytes(words[0]), i32::from_be_bytes(words[1]));
+		assert_eq!((nu as usize, nv as usize), (grid.kmax + 1, grid.jmax + 1));
+		let floats: Vec<f32> = words[2..].iter().map(|w: &[u8; 4]| f32::from_be_bytes(*w)).collect();
+		assert_eq!(floats.len(), (nu * nv * 6) as usize);
+
+		for (vertex, jet) in floats.chunks_exact(6).zip(grid.values.iter().flatten()) {
+			let expected: Vec<f64> = [jet.x().f(), jet.y().f(), jet.z().f()].into_iter().chain(jet.normal()).collect();
+			for (got, want) in vertex.iter().zip(expected) {
+				assert!((*got as f64 - want).abs() < 1e-6, "{vertex:?} != {jet:?}");
 			};
-			if j < 0 || j >= N_STRIPS.get().into() {
-				panic!("evert -parts: bad strip number {}; must be in range 0..{}", j, N_STRIPS.get() - 1);
+		};
+	}
+	#[test]
+	fn stbbp_binary_round_trip() {
+		let config: crate::nstrip::EversionConfig = crate::nstrip::EversionConfig::new(8, true, true);
+		let grid: crate::Grid = crate::Eversion { config, ..Default::default() }.grid(crate::Sto::UnPush, 0.7);
+		let words: Vec<[u8; 4]> = read_binary(&print_binary(&grid, &config), "STBBP");
+		let floats: Vec<f32> = words.iter().map(|w: &[u8; 4]| f32::from_be_bytes(*w)).collect();
+
+		// 16 control points, then an (s, t) pair per corner
+		assert_eq!(floats.len(), grid.jmax * grid.kmax * (16 * 3 + 4 * 2));
+		let mut patches = floats.chunks_exact(16 * 3 + 4 * 2);
+		for j in 0..grid.jmax {
+			for k in 0..grid.kmax {
+				let patch: &[f32] = patches.next().unwrap();
+				let v: &Vec<Vec<crate::twojetvec::TwoJetVec>> = &grid.values;
+				let net = v[j][k].brezier_patch(v[j][k + 1], v[j + 1][k], v[j + 1][k + 1], grid.du, grid.dv);
+				for (got, want) in patch[..48].chunks_exact(3).zip(net.iter()) {
+					for (got, want) in got.iter().zip(want.xyz()) { assert!((*got as f64 - want).abs() < 1e-6); };
+				};
+				let st: [f64; 8] = [grid.u(j), grid.v(k), grid.u(j + 1), grid.v(k), grid.u(j), grid.v(k + 1), grid.u(j + 1), grid.v(k + 1)];
+				assert_eq!(patch[48..].to_vec(), st.map(|x: f64| x as f32).to_vec());
 			};
-			if idx != 0 {
-				partlist[j as usize] |= bits;
-			}
- 		};
-	};
-	return partlist;
-}
\ No newline at end of file
+		};
+	}
+	#[test]
+	fn parse_parts() {
+		assert_eq!(super::parse_parts("+0-0+2+4+6".chars().collect(), 8), Ok(vec![3, 0, 1, 0, 1, 0, 1, 0]));
+		assert_eq!(super::parse_parts("*".chars().collect(), 4), Ok(vec![3, 3, 3, 3]));
+		assert_eq!(super::parse_parts("-*, 1".chars().collect(), 3), Ok(vec![2, 3, 2]));
+		assert!(super::parse_parts("+8".chars().collect(), 8).is_err());
+		assert!(super::parse_parts("+x".chars().collect(), 8).is_err());
+	}
+	#[test]
+	fn whole_sphere_is_closed() {
+		// every replica of the unit's boundary lands on another replica's boundary
+		let eversion: crate::Eversion = crate::Eversion::default();
+		let grid: crate::Grid = eversion.grid(crate::Sto::Corrugate, 0.0);
+		let parts: Vec<super::Part> = super::part_transforms(&super::parse_parts(vec!['*'], 8).unwrap(), 8);
+		assert_eq!(parts.len(), 16);
+		let p = |part: &super::Part, j: usize, k: usize| -> [f64; 3] {
+			let jet: crate::twojetvec::TwoJetVec = grid.values[j][k];
+			part.transform.apply_point([jet.x().f(), jet.y().f(), jet.z().f()])
+		};
+		let near = |a: [f64; 3], b: [f64; 3]| -> bool { (0..3).all(|d: usize| (a[d] - b[d]).abs() < 1e-9) };
+		for a in parts.iter() {
+			for k in 0..=grid.kmax {
+				// the equator edge u=1 of each strip is shared with the opposite hemisphere
+				assert!(parts.iter().any(|b: &super::Part| b.sign != a.sign && (0..=grid.kmax).any(|kb: usize| near(p(a, grid.jmax, k), p(b, grid.jmax, kb)))), "{}{} equator", a.sign, a.strip);
+			};
+			for j in 0..=grid.jmax {
+				// the v=1 edge of each strip is the v=0 edge of its neighbour
+				assert!(parts.iter().any(|b: &super::Part| b.sign == a.sign && near(p(a, j, grid.kmax), p(b, j, 0))), "{}{} seam", a.sign, a.strip);
+				// ...and strips don't overlap
+				assert!(j == 0 || !parts.iter().any(|b: &super::Part| near(p(a, j, grid.kmax / 2), p(b, j, 0))), "{}{} overlaps", a.sign, a.strip);
+			};
+		};
+	}
+	#[test]
+	fn bezier_corners_match_jets() {
+		let config: crate::nstrip::EversionConfig = crate::nstrip::EversionConfig::new(8, false, true);
+		let eversion: crate::Eversion = crate::Eversion { config, ..Default::default() };
+		let grid: crate::Grid = eversion.grid(crate::Sto::Twist, 0.3);
+
+		let mut sink = crate::WriterSink::new(Vec::<u8>::new(), std::io::sink());
+		super::print_scene(&grid, vec![], &config, &mut sink).unwrap();
+		let text: String = String::from_utf8(sink.into_inner().0).unwrap();
+
+		let mut lines = text.lines().filter(|l: &&str| !l.is_empty());
+		assert_eq!(lines.next(), Some("{ STBBP"));
+
+		for j in 0..grid.jmax {
+			for k in 0..grid.kmax {
+				let net: Vec<[f64; 3]> = (0..16).map(|_| {
+					let xyz: Vec<f64> = lines.next().unwrap().split_whitespace().map(|n: &str| n.parse().unwrap()).collect();
+					[xyz[0], xyz[1], xyz[2]]
+				}).collect();
+				let st: Vec<f64> = lines.next().unwrap().split_whitespace().map(|n: &str| n.parse().unwrap()).collect();
+				assert_eq!(st, vec![grid.u(j), grid.v(k), grid.u(j + 1), grid.v(k), grid.u(j), grid.v(k + 1), grid.u(j + 1), grid.v(k + 1)]);
+
+				for (idx, (jj, kk)) in [(0, (j, k)), (3, (j + 1, k)), (12, (j, k + 1)), (15, (j + 1, k + 1))] {
+					let jet: crate::twojetvec::TwoJetVec = grid.values[jj][kk];
+					let f: [f64; 3] = [jet.x().f(), jet.y().f(), jet.z().f()];
+					for d in 0..3 {
+						assert!((net[idx][d] - f[d]).abs() < 1e-6, "patch ({j}, {k}) corner {idx}: {:?} != {:?}", net[idx], f);
+					};
+				};
+				// the first control points along each edge carry the tangents of the corner jet
+				let jet: crate::twojetvec::TwoJetVec = grid.values[j][k];
+				let (fu, fv): ([f64; 3], [f64; 3]) = ([jet.x().fu(), jet.y().fu(), jet.z().fu()], [jet.x().fv(), jet.y().fv(), jet.z().fv()]);
+				for d in 0..3 {
+					assert!((net[1][d] - net[0][d] - fu[d] * grid.du / 3.0).abs() < 2e-6);
+					assert!((net[4][d] - net[0][d] - fv[d] * grid.dv / 3.0).abs() < 2e-6);
+				};
+			};
+		};
+		assert_eq!(lines.next(), Some("}"));
+	}
+}
@@ -0,0 +1,139 @@
+use crate::{twojet::TwoJet, twojetvec::TwoJetVec};
+
+/// Affine transformation of 3-space as a 4x4 matrix.
+///
+/// Follows the Geomview convention: points are row vectors multiplied on the left, `p' = p * M`,
+/// so the translation lives in the last row and `a.then(b)` applies `a` first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub m: [[f64; 4]; 4],
+}
+
+impl Default for Transform {
+    fn default() -> Self { Self::identity() }
+}
+
+impl Transform {
+    pub fn new(m: [[f64; 4]; 4]) -> Self { Self { m } }
+    pub fn identity() -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+    pub fn scale(s: f64) -> Self { Self::scale_xyz(s, s, s) }
+    pub fn scale_xyz(x: f64, y: f64, z: f64) -> Self {
+        Self::new([
+            [  x, 0.0, 0.0, 0.0],
+            [0.0,   y, 0.0, 0.0],
+            [0.0, 0.0,   z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+    pub fn translate(x: f64, y: f64, z: f64) -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [  x,   y,   z, 1.0],
+        ])
+    }
+    /// Right-handed rotation about +X by `angle` radians.
+    pub fn rotate_x(angle: f64) -> Self {
+        let (s, c): (f64, f64) = angle.sin_cos();
+        Self::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0,   c,   s, 0.0],
+            [0.0,  -s,   c, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+    /// Right-handed rotation about +Y by `angle` radians.
+    pub fn rotate_y(angle: f64) -> Self {
+        let (s, c): (f64, f64) = angle.sin_cos();
+        Self::new([
+            [  c, 0.0,  -s, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [  s, 0.0,   c, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+    /// Right-handed rotation about +Z by `angle` radians.
+    pub fn rotate_z(angle: f64) -> Self {
+        let (s, c): (f64, f64) = angle.sin_cos();
+        Self::new([
+            [  c,   s, 0.0, 0.0],
+            [ -s,   c, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+    /// `self` followed by `rhs`.
+    pub fn then(&self, rhs: &Self) -> Self {
+        let mut m: [[f64; 4]; 4] = [[0.0; 4]; 4];
+        for (r, row) in m.iter_mut().enumerate() {
+            for (c, cell) in row.iter_mut().enumerate() {
+                *cell = (0..4).map(|i: usize| self.m[r][i] * rhs.m[i][c]).sum();
+            };
+        };
+        return Self::new(m);
+    }
+    pub fn is_identity(&self) -> bool { *self == Self::identity() }
+    pub fn apply_point(&self, p: [f64; 3]) -> [f64; 3] {
+        let m: &[[f64; 4]; 4] = &self.m;
+        let w: f64 = p[0] * m[0][3] + p[1] * m[1][3] + p[2] * m[2][3] + m[3][3];
+        let mut o: [f64; 3] = self.apply_vector(p);
+        for (c, o) in o.iter_mut().enumerate() { *o = (*o + m[3][c]) / w; };
+        return o;
+    }
+    /// Applies the linear part only, as for tangents.
+    pub fn apply_vector(&self, v: [f64; 3]) -> [f64; 3] {
+        let m: &[[f64; 4]; 4] = &self.m;
+        [0, 1, 2].map(|c: usize| v[0] * m[0][c] + v[1] * m[1][c] + v[2] * m[2][c])
+    }
+    /// Maps the normal `n` of a surface onto the normal of the transformed surface, renormalized.
+    ///
+    /// Uses the cofactors of the linear part, so a reflection flips the normal along with the
+    /// orientation of the surface, just like `fu x fv` of the transformed jets would.
+    pub fn apply_normal(&self, n: [f64; 3]) -> [f64; 3] {
+        let m: &[[f64; 4]; 4] = &self.m;
+        let cof = |r: usize, c: usize| -> f64 {
+            let (r1, r2, c1, c2): (usize, usize, usize, usize) = ((r + 1) % 3, (r + 2) % 3, (c + 1) % 3, (c + 2) % 3);
+            m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1]
+        };
+        let o: [f64; 3] = [0, 1, 2].map(|c: usize| n[0] * cof(0, c) + n[1] * cof(1, c) + n[2] * cof(2, c));
+        let mut s: f64 = o[0] * o[0] + o[1] * o[1] + o[2] * o[2];
+        if s > 0.0 { s = (1.0 / s).sqrt(); };
+        return o.map(|x: f64| x * s);
+    }
+    /// Transforms a surface jet; derivatives only see the linear part.
+    pub fn apply_jet(&self, p: &TwoJetVec) -> TwoJetVec {
+        let m: &[[f64; 4]; 4] = &self.m;
+        let (x, y, z): (TwoJet, TwoJet, TwoJet) = (p.x(), p.y(), p.z());
+        let axis = |c: usize| -> TwoJet {
+            TwoJet::new(
+                x.f()   * m[0][c] + y.f()   * m[1][c] + z.f()   * m[2][c] + m[3][c],
+                x.fu()  * m[0][c] + y.fu()  * m[1][c] + z.fu()  * m[2][c],
+                x.fv()  * m[0][c] + y.fv()  * m[1][c] + z.fv()  * m[2][c],
+                Some(x.fuv() * m[0][c] + y.fuv() * m[1][c] + z.fuv() * m[2][c]),
+            )
+        };
+        return TwoJetVec::new(axis(0), axis(1), axis(2));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn composition_order() {
+        use crate::transform::Transform;
+        let t: Transform = Transform::scale(2.0).then(&Transform::rotate_z(std::f64::consts::FRAC_PI_2)).then(&Transform::translate(0.0, 0.0, 1.0));
+        let p: [f64; 3] = t.apply_point([1.0, 0.0, 0.0]);
+        assert!((p[0] - 0.0).abs() < 1e-12 && (p[1] - 2.0).abs() < 1e-12 && (p[2] - 1.0).abs() < 1e-12, "{p:?}");
+        // a mirror flips the surface, and with it the normal
+        assert_eq!(Transform::scale_xyz(1.0, 1.0, -1.0).apply_normal([0.0, 0.0, 1.0]), [0.0, 0.0, 1.0]);
+        assert_eq!(Transform::scale_xyz(-1.0, 1.0, 1.0).apply_normal([0.0, 0.0, 1.0]), [0.0, 0.0, -1.0]);
+    }
+}
@@ -172,10 +172,7 @@ impl TwoJet {
     pub fn new(d: f64, du: f64, dv: f64, duv: Option<f64>) -> Self {
         return Self {
             f: d, fu: du, fv: dv,
-            fuv: match duv {
-                Some(duv) => { duv },
-                None => { 0.0 },
-            },
+            fuv: duv.unwrap_or(0.0),
         }
     }
     pub fn zero() -> Self {
@@ -1,4 +1,5 @@
-use crate::{twojet::TwoJet, points::{SplinePoint, BrezierPoint}, threejetvec::ThreeJetVec, spline::PrintableSpline};
+use crate::{twojet::TwoJet, points::{SplinePoint, BrezierPoint}, threejetvec::ThreeJetVec, spline::PrintableSpline, nstrip::EversionConfig};
+use std::io::Write;
 
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -223,6 +224,34 @@ impl TwoJetVec {
 			self.z.brezier_dim(ps, pus, pvs, puvs),
         )
     }
+    /// Control net of the bicubic Bezier patch spanning this corner and `v01`, `v10`, `v11`,
+    /// whose corners and edge derivatives match the jets; `us` x `vs` is the (u, v) size of the patch.
+    ///
+    /// Points are listed row by row, u varying fastest.
+    pub fn brezier_patch(&self, v01: TwoJetVec,  v10: TwoJetVec, v11: TwoJetVec, us: f64, vs: f64) -> [BrezierPoint; 16] {
+        let v00: &Self = self;
+        return [
+            v00.brezier_point(1.0, 0.0, 0.0, 0.0),
+            v00.brezier_point(1.0,  us, 0.0, 0.0),
+            v10.brezier_point(1.0, -us, 0.0, 0.0),
+            v10.brezier_point(1.0, 0.0, 0.0, 0.0),
+
+            v00.brezier_point(1.0, 0.0,  vs, 0.0),
+            v00.brezier_point(1.0,  us,  vs,  us * vs),
+            v10.brezier_point(1.0, -us,  vs, -us * vs),
+            v10.brezier_point(1.0, 0.0,  vs, 0.0),
+
+            v01.brezier_point(1.0, 0.0, -vs, 0.0),
+            v01.brezier_point(1.0,  us, -vs, -us * vs),
+            v11.brezier_point(1.0, -us, -vs,  us * vs),
+            v11.brezier_point(1.0, 0.0, -vs, 0.0),
+
+            v01.brezier_point(1.0, 0.0, 0.0, 0.0),
+            v01.brezier_point(1.0,  us, 0.0, 0.0),
+            v11.brezier_point(1.0, -us, 0.0, 0.0),
+            v11.brezier_point(1.0, 0.0, 0.0, 0.0),
+        ];
+    }
     pub fn point(&self, ps: Option<f64>) -> SplinePoint {
         let x:	f64 = Into::<f64>::into(self.x()) * ps.unwrap_or(1.0);
  		let y:	f64 = Into::<f64>::into(self.y()) * ps.unwrap_or(1.0);
@@ -234,6 +263,15 @@ impl TwoJetVec {
  		if s > 0.0 { s = (1.0 / s).sqrt(); };
         return SplinePoint::new(x, y, z, nx, ny, nz, s);
     }
+    /// Unit normal, fu x fv normalized; zero where the surface degenerates (e.g. at the poles).
+    pub fn normal(&self) -> [f64; 3] {
+        let nx: f64 = self.y.fu() * self.z.fv() - self.z.fu() * self.y.fv();
+        let ny: f64 = self.z.fu() * self.x.fv() - self.x.fu() * self.z.fv();
+        let nz: f64 = self.x.fu() * self.y.fv() - self.y.fu() * self.x.fv();
+        let mut s: f64 = nx * nx + ny * ny + nz * nz;
+        if s > 0.0 { s = (1.0 / s).sqrt(); };
+        return [nx * s, ny * s, nz * s];
+    }
     pub fn x(&self) -> TwoJet { self.x }
     pub fn y(&self) -> TwoJet { self.y }
     pub fn z(&self) -> TwoJet { self.z }
@@ -244,41 +282,15 @@ impl TwoJetVec {
 }
 
 impl PrintableSpline for TwoJetVec {
-    fn print_spline(&self, v01: TwoJetVec,  v10: TwoJetVec, v11: TwoJetVec, us: f64, vs: f64, s0: f64, s1: f64, t0: f64, t1: f64) {
-        let v00: &Self = self;
-        if crate::nstrip::EasyAtomic::get(&crate::nstrip::BINARY) {
-            let magic: [BrezierPoint; 16] = [
-                v00.brezier_point(1.0, 0.0, 0.0, 0.0),
-                v00.brezier_point(1.0,  us, 0.0, 0.0),
-                v10.brezier_point(1.0, -us, 0.0, 0.0),
-                v10.brezier_point(1.0, 0.0, 0.0, 0.0),
-                
-                v00.brezier_point(1.0, 0.0,  vs, 0.0),
-                v00.brezier_point(1.0,  us,  vs,  us * vs),
-                v10.brezier_point(1.0, -us,  vs, -us * vs),
-                v10.brezier_point(1.0, 0.0,  vs, 0.0),
-                
-                v01.brezier_point(1.0, 0.0, -vs, 0.0),
-                v01.brezier_point(1.0,  us, -vs, -us * vs),
-                v11.brezier_point(1.0, -us, -vs,  us * vs),
-                v11.brezier_point(1.0, 0.0, -vs, 0.0),
-
-                v01.brezier_point(1.0, 0.0, 0.0, 0.0),
-                v01.brezier_point(1.0,  us, 0.0, 0.0),
-                v11.brezier_point(1.0, -us, 0.0, 0.0),
-                v11.brezier_point(1.0, 0.0, 0.0, 0.0),
-            ];
-            if crate::nstrip::EasyAtomic::get(&crate::nstrip::BINARY) {
-                for bp in magic { std::io::Write::write(&mut std::io::stdout(), &bp.as_bytes()).unwrap(); };
-                println!()
-            } else {
-                for bp in magic { println!("{}", bp.g()) };
-                println!("{s0} {t0}  {s1} {t0}  {s0} {t1}  {s1} {t1}\n", s0=s0, t0=t0, s1=s1, t1=t1);
-            }
+    fn print_spline(&self, v01: TwoJetVec,  v10: TwoJetVec, v11: TwoJetVec, us: f64, vs: f64, s0: f64, s1: f64, t0: f64, t1: f64, config: &EversionConfig, out: &mut dyn Write) -> std::io::Result<()> {
+        let magic: [BrezierPoint; 16] = self.brezier_patch(v01, v10, v11, us, vs);
+        if config.binary {
+            for bp in magic { out.write_all(&bp.as_bytes())?; };
+            for st in [s0, t0, s1, t0, s0, t1, s1, t1] { out.write_all(&(st as f32).to_be_bytes())?; };
         } else {
-            let magic: [SplinePoint; 4] = [ v00.point(None), v10.point(None), v11.point(None), v01.point(None) ];
-            for sp in magic { println!("{}", sp) };
-            if !crate::nstrip::EasyAtomic::get(&crate::nstrip::BINARY) { println!() };
+            for bp in magic { writeln!(out, "{}", bp.g())? };
+            writeln!(out, "{s0} {t0}  {s1} {t0}  {s0} {t1}  {s1} {t1}\n", s0=s0, t0=t0, s1=s1, t1=t1)?;
         }
+        return Ok(());
     }
-}
\ No newline at end of file
+}